@@ -1,16 +1,8 @@
-if [ $# -ne 1 ]; then
-    echo "Usage: $0 <SRC_DIR>"
+if [ $# -lt 1 ]; then
+    echo "Usage: $0 <SRC_DIR>..."
     exit 1
 fi
-SRC_DIR=$1
+SCRIPT_DIR=$(dirname "$0")
 
-# glob all *.lua file
-for file in $(find $SRC_DIR -name "*.lua"); do
-    echo "Testing $file"
-    sh ./scripts/lint_single.sh $file binary
-
-    if [ $? -ne 0 ]; then
-        echo "Failed to test $file"
-        exit 1
-    fi
-done
+# lualint walks the directories itself and lints the files in parallel
+$SCRIPT_DIR/../target/debug/lualint run --rules $SCRIPT_DIR/all_rules.jsonc "$@"
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use lualint::driver::RuleReport;
+
+use super::walk::normalize_path;
+
+pub const DEFAULT_BASELINE_FILE: &str = "lualint-baseline.json";
+
+/// A report recorded by `lualint baseline`. Reports are matched by file, rule
+/// and fingerprint, so they still match after lines are added above them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BaselineEntry {
+    pub file: String,
+    pub rule: String,
+    pub fingerprint: String,
+    // only kept to make the file readable
+    pub message: String,
+}
+
+/// Known reports that `run --baseline` does not show. The same report may be
+/// recorded several times, e.g. for identical lines, each entry matches once.
+#[derive(Clone, Debug, Default)]
+pub struct Baseline {
+    pub entries: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    pub fn from_reports(reports: &[RuleReport]) -> Self {
+        let entries = reports.iter().map(|rule_report| BaselineEntry {
+            file: normalize_path(&rule_report.report.pos.file),
+            rule: rule_report.rule.clone(),
+            fingerprint: rule_report.fingerprint.clone(),
+            message: rule_report.report.msg.clone(),
+        });
+        Self { entries: entries.collect() }
+    }
+
+    pub fn from_json(src: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(src).map_err(|e| e.to_string())?;
+        let entries = value["entries"].as_array().ok_or("expected an `entries` list")?;
+        let entries = entries.iter().map(|entry| {
+            let field = |key: &str| -> Result<String, String> {
+                entry[key]
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("baseline entry without `{key}`: {entry}"))
+            };
+            Ok(BaselineEntry {
+                file: normalize_path(&field("file")?),
+                rule: field("rule")?,
+                fingerprint: field("fingerprint")?,
+                message: entry["message"].as_str().unwrap_or_default().to_string(),
+            })
+        });
+        Ok(Self { entries: entries.collect::<Result<_, String>>()? })
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let src = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        Self::from_json(&src).map_err(|e| format!("{path}: {e}"))
+    }
+
+    pub fn to_json(&self) -> Value {
+        let entries = self.entries.iter().map(|entry| {
+            json!({
+                "file": entry.file,
+                "rule": entry.rule,
+                "fingerprint": entry.fingerprint,
+                "message": entry.message,
+            })
+        });
+        json!({ "version": 1, "entries": entries.collect::<Vec<_>>() })
+    }
+
+    /// Drops the reports of `file` recorded in the baseline from `reports` and
+    /// returns the entries of `file` that no report matched.
+    pub fn filter(&self, file: &str, reports: &mut Vec<RuleReport>) -> Vec<BaselineEntry> {
+        let file = normalize_path(file);
+        let key = |rule: &str, fingerprint: &str| (rule.to_string(), fingerprint.to_string());
+        let mut remaining: HashMap<(String, String), usize> = HashMap::new();
+        for entry in self.entries.iter().filter(|entry| entry.file == file) {
+            *remaining.entry(key(&entry.rule, &entry.fingerprint)).or_insert(0) += 1;
+        }
+        reports.retain(|rule_report| {
+            match remaining.get_mut(&key(&rule_report.rule, &rule_report.fingerprint)) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            }
+        });
+        // the last entries of a fingerprint are the stale ones
+        let mut stale = vec![];
+        for entry in self.entries.iter().rev().filter(|entry| entry.file == file) {
+            if let Some(count) = remaining.get_mut(&key(&entry.rule, &entry.fingerprint)) {
+                if *count > 0 {
+                    *count -= 1;
+                    stale.push(entry.clone());
+                }
+            }
+        }
+        stale.reverse();
+        stale
+    }
+}
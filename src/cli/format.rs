@@ -0,0 +1,197 @@
+use std::{collections::HashMap, io};
+
+use lualint::{
+    driver::{fingerprint, RuleReport},
+    rules::{self, LintReport, ReportLevel},
+};
+use serde_json::{json, Value};
+
+/// How `lualint run` prints the reports. All but `text` are written once for
+/// all files, after every file has been linted.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// rustc-style snippets, one lint report per file
+    #[default]
+    Text,
+    /// `file:line:col: severity: rule: message` lines for editors
+    Compact,
+    /// an array of reports
+    Json,
+    /// SARIF 2.1.0 for code scanning
+    Sarif,
+    /// checkstyle XML for Jenkins
+    Checkstyle,
+    /// GitLab Code Quality
+    Gitlab,
+}
+
+pub fn write_reports(
+    format: OutputFormat,
+    reports: &[RuleReport],
+    writer: &mut dyn io::Write,
+) -> io::Result<()> {
+    match format {
+        // printed file by file by `lint_file`
+        OutputFormat::Text => Ok(()),
+        OutputFormat::Compact => write_compact(reports, writer),
+        OutputFormat::Json => write_json(&to_json(reports), writer),
+        OutputFormat::Sarif => write_json(&to_sarif(reports), writer),
+        OutputFormat::Checkstyle => write_checkstyle(reports, writer),
+        OutputFormat::Gitlab => write_json(&to_gitlab(reports), writer),
+    }
+}
+
+// line 0 means the whole file, most formats want a real position
+fn line_col(report: &LintReport) -> (usize, usize) {
+    (report.pos.line.max(1), report.pos.col.max(1))
+}
+
+fn write_json(value: &Value, writer: &mut dyn io::Write) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *writer, value)?;
+    writeln!(writer)
+}
+
+fn write_compact(reports: &[RuleReport], writer: &mut dyn io::Write) -> io::Result<()> {
+    for RuleReport { rule, report, .. } in reports {
+        let (line, col) = line_col(report);
+        writeln!(
+            writer,
+            "{}:{}:{}: {}: {}: {}",
+            report.pos.file, line, col, report.level, rule, report.msg
+        )?;
+    }
+    Ok(())
+}
+
+fn to_json(reports: &[RuleReport]) -> Value {
+    let reports = reports.iter().map(|RuleReport { rule, report, fingerprint }| {
+        let fix = report.fix.as_ref().map(|fix| {
+            let edits = fix.edits.iter().map(|edit| {
+                json!({ "start": edit.start, "end": edit.end, "replacement": edit.replacement })
+            });
+            json!({ "safe": fix.safe, "edits": edits.collect::<Vec<_>>() })
+        });
+        json!({
+            "file": report.pos.file,
+            "rule": rule,
+            "severity": report.level.to_string(),
+            "line": report.pos.line,
+            "column": report.pos.col,
+            "message": report.msg,
+            "fingerprint": fingerprint,
+            "fix": fix,
+        })
+    });
+    Value::Array(reports.collect())
+}
+
+fn to_sarif(reports: &[RuleReport]) -> Value {
+    let mut rule_ids: Vec<&str> =
+        reports.iter().map(|rule_report| rule_report.rule.as_str()).collect();
+    rule_ids.sort();
+    rule_ids.dedup();
+    let all_rules = rules::ALL_RULES.lock().unwrap();
+    let rules = rule_ids.iter().map(|id| match all_rules.get::<str>(id) {
+        Some(info) => json!({ "id": id, "shortDescription": { "text": info.description } }),
+        None => json!({ "id": id }),
+    });
+    let results = reports.iter().map(|RuleReport { rule, report, .. }| {
+        let (line, col) = line_col(report);
+        json!({
+            "ruleId": rule,
+            "level": match report.level {
+                ReportLevel::Info => "note",
+                ReportLevel::Warning => "warning",
+                ReportLevel::Error => "error",
+            },
+            "message": { "text": report.msg },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": report.pos.file },
+                    "region": { "startLine": line, "startColumn": col },
+                },
+            }],
+        })
+    });
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "lualint",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.collect::<Vec<_>>(),
+                },
+            },
+            "results": results.collect::<Vec<_>>(),
+        }],
+    })
+}
+
+fn to_gitlab(reports: &[RuleReport]) -> Value {
+    // GitLab needs unique fingerprints: identical reports on identical lines of
+    // a file are told apart by their order, which survives unrelated edits
+    let mut occurrences: HashMap<(&str, &str), usize> = HashMap::new();
+    let reports = reports.iter().map(|RuleReport { rule, report, fingerprint: id }| {
+        let (line, _) = line_col(report);
+        let occurrence = occurrences.entry((&report.pos.file, id)).or_insert(0);
+        *occurrence += 1;
+        json!({
+            "description": report.msg,
+            "check_name": rule,
+            "fingerprint": fingerprint(&[&report.pos.file, id, &occurrence.to_string()]),
+            "severity": match report.level {
+                ReportLevel::Info => "info",
+                ReportLevel::Warning => "minor",
+                ReportLevel::Error => "major",
+            },
+            "location": { "path": report.pos.file, "lines": { "begin": line } },
+        })
+    });
+    Value::Array(reports.collect())
+}
+
+fn write_checkstyle(reports: &[RuleReport], writer: &mut dyn io::Write) -> io::Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, r#"<checkstyle version="4.3">"#)?;
+    let mut current_file: Option<&str> = None;
+    for RuleReport { rule, report, .. } in reports {
+        if current_file != Some(report.pos.file.as_str()) {
+            if current_file.is_some() {
+                writeln!(writer, "  </file>")?;
+            }
+            writeln!(writer, r#"  <file name="{}">"#, escape_xml(&report.pos.file))?;
+            current_file = Some(&report.pos.file);
+        }
+        let (line, col) = line_col(report);
+        writeln!(
+            writer,
+            r#"    <error line="{}" column="{}" severity="{}" message="{}" source="lualint.{}"/>"#,
+            line,
+            col,
+            report.level,
+            escape_xml(&report.msg),
+            escape_xml(rule)
+        )?;
+    }
+    if current_file.is_some() {
+        writeln!(writer, "  </file>")?;
+    }
+    writeln!(writer, "</checkstyle>")
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
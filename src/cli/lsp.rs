@@ -0,0 +1,389 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use log::{error, trace};
+use lualint::{
+    config::{Config, ConfigLoader, CONFIG_FILE_NAMES},
+    lint::Linter,
+    rules::{self, ReportLevel},
+};
+use serde_json::{json, Value};
+
+use super::{build_linter, collect_reports, drive, RuleReport};
+
+// https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/
+const METHOD_NOT_FOUND: i64 = -32601;
+const TEXT_DOCUMENT_SYNC_FULL: u64 = 1;
+const MESSAGE_TYPE_ERROR: u64 = 1;
+
+/// An open editor buffer and the reports of its last lint.
+struct Document {
+    text: String,
+    reports: Vec<RuleReport>,
+}
+
+/// Serves `lualint lsp`: lints the open buffers with the rules of the config
+/// files that apply to them, `override_config` (from `--rules`) on top.
+pub struct Server<'a> {
+    writer: &'a mut dyn Write,
+    override_config: Config,
+    config_loader: ConfigLoader,
+    documents: HashMap<String, Document>,
+    next_request_id: u64,
+}
+
+impl<'a> Server<'a> {
+    pub fn new(writer: &'a mut dyn Write, override_config: Config) -> Self {
+        Self {
+            writer,
+            override_config,
+            config_loader: ConfigLoader::default(),
+            documents: HashMap::new(),
+            next_request_id: 0,
+        }
+    }
+
+    /// Handles messages from `reader` until the client asks to exit or
+    /// closes the stream.
+    pub fn serve(&mut self, reader: &mut dyn BufRead) -> io::Result<()> {
+        while let Some(message) = read_message(reader)? {
+            trace!("lsp <- {}", message);
+            if !self.handle(&message)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns false on `exit`.
+    fn handle(&mut self, message: &Value) -> io::Result<bool> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let id = match message.get("id") {
+            // responses to our own requests, e.g. `client/registerCapability`
+            Some(_) if message.get("method").is_none() => return Ok(true),
+            Some(id) => id,
+            None => {
+                self.handle_notification(method, params)?;
+                return Ok(method != "exit");
+            }
+        };
+        let result = match method {
+            "initialize" => {
+                self.register_config_watcher(params)?;
+                json!({
+                    "capabilities": {
+                        "textDocumentSync": TEXT_DOCUMENT_SYNC_FULL,
+                        "codeActionProvider": true,
+                        "hoverProvider": true,
+                    },
+                    "serverInfo": { "name": "lualint", "version": env!("CARGO_PKG_VERSION") },
+                })
+            }
+            "shutdown" => Value::Null,
+            "textDocument/codeAction" => self.code_actions(params),
+            "textDocument/hover" => self.hover(params),
+            _ => {
+                let message = format!("{method} is not supported");
+                let error = json!({ "code": METHOD_NOT_FOUND, "message": message });
+                self.send(&json!({ "jsonrpc": "2.0", "id": id, "error": error }))?;
+                return Ok(true);
+            }
+        };
+        self.send(&json!({ "jsonrpc": "2.0", "id": id, "result": result }))?;
+        Ok(true)
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) -> io::Result<()> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.update(uri, text.to_string())
+            }
+            "textDocument/didChange" => {
+                // full sync: the last change holds the whole text
+                match params["contentChanges"].as_array().and_then(|changes| changes.last()) {
+                    Some(change) => {
+                        let text = change["text"].as_str().unwrap_or_default();
+                        self.update(uri, text.to_string())
+                    }
+                    None => Ok(()),
+                }
+            }
+            "textDocument/didSave" if is_config_file(uri) => self.reload_config(),
+            "textDocument/didClose" => {
+                self.documents.remove(uri);
+                self.publish(uri, &[], "")
+            }
+            "workspace/didChangeWatchedFiles" => self.reload_config(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Asks the client to notify us of config file changes, if it can.
+    fn register_config_watcher(&mut self, params: &Value) -> io::Result<()> {
+        let capabilities = &params["capabilities"]["workspace"]["didChangeWatchedFiles"];
+        if capabilities["dynamicRegistration"] != true {
+            return Ok(());
+        }
+        let watchers: Vec<Value> = CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| json!({ "globPattern": format!("**/{name}") }))
+            .collect();
+        self.next_request_id += 1;
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "id": format!("lualint-{}", self.next_request_id),
+            "method": "client/registerCapability",
+            "params": {
+                "registrations": [{
+                    "id": "lualint-config",
+                    "method": "workspace/didChangeWatchedFiles",
+                    "registerOptions": { "watchers": watchers },
+                }],
+            },
+        }))
+    }
+
+    /// Forgets the loaded config files and lints the open buffers again.
+    fn reload_config(&mut self) -> io::Result<()> {
+        self.config_loader = ConfigLoader::default();
+        let uris: Vec<String> = self.documents.keys().cloned().collect();
+        for uri in uris {
+            let text = std::mem::take(&mut self.documents.get_mut(&uri).unwrap().text);
+            self.update(&uri, text)?;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, uri: &str, text: String) -> io::Result<()> {
+        if is_config_file(uri) || !uri.ends_with(".lua") {
+            return Ok(());
+        }
+        let path = uri_to_path(uri);
+        let reports = match self.load_linter(&path) {
+            Ok(Some(mut linter)) => {
+                // the buffer may differ from the file, only its path is used
+                let path = path.to_string_lossy();
+                drive(&text, &mut linter);
+                collect_reports(&path, &text, &mut linter, None, None)
+            }
+            Ok(None) => vec![],
+            Err(e) => {
+                self.show_error(&e)?;
+                vec![]
+            }
+        };
+        self.publish(uri, &reports, &text)?;
+        self.documents.insert(uri.to_string(), Document { text, reports });
+        Ok(())
+    }
+
+    /// A linter for `path` with the config files in effect, `None` if it is excluded.
+    fn load_linter(&mut self, path: &Path) -> Result<Option<Linter>, String> {
+        let mut config = self.config_loader.load_for_file(path)?;
+        config.merge(&self.override_config);
+        if config.is_excluded(path) {
+            return Ok(None);
+        }
+        // `build_linter` logs the reason, which ends up in the client's log
+        match build_linter(&config) {
+            Some(linter) => Ok(Some(linter)),
+            None => Err(format!("lualint: invalid config for {}", path.display())),
+        }
+    }
+
+    fn publish(&mut self, uri: &str, reports: &[RuleReport], text: &str) -> io::Result<()> {
+        let diagnostics: Vec<Value> = reports
+            .iter()
+            .map(|RuleReport { rule, report, .. }| {
+                json!({
+                    "range": report_range(text, report.pos.line, report.pos.col),
+                    "severity": match report.level {
+                        ReportLevel::Error => 1,
+                        ReportLevel::Warning => 2,
+                        ReportLevel::Info => 3,
+                    },
+                    "code": rule,
+                    "source": "lualint",
+                    "message": report.msg,
+                })
+            })
+            .collect();
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }))
+    }
+
+    fn show_error(&mut self, message: &str) -> io::Result<()> {
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "method": "window/showMessage",
+            "params": { "type": MESSAGE_TYPE_ERROR, "message": message },
+        }))
+    }
+
+    /// A quick fix for every report with a fix on the requested lines.
+    fn code_actions(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let document = match self.documents.get(uri) {
+            Some(document) => document,
+            None => return json!([]),
+        };
+        let first_line = params["range"]["start"]["line"].as_u64().unwrap_or(0) as usize + 1;
+        let last_line = params["range"]["end"]["line"].as_u64().unwrap_or(0) as usize + 1;
+        let actions = document.reports.iter().filter_map(|RuleReport { rule, report, .. }| {
+            let fix = report.fix.as_ref()?;
+            if !(first_line..=last_line).contains(&report.pos.line) {
+                return None;
+            }
+            let edits: Vec<Value> = fix
+                .edits
+                .iter()
+                .map(|edit| {
+                    json!({
+                        "range": {
+                            "start": offset_to_position(&document.text, edit.start),
+                            "end": offset_to_position(&document.text, edit.end),
+                        },
+                        "newText": edit.replacement,
+                    })
+                })
+                .collect();
+            Some(json!({
+                "title": format!("Fix {rule}: {}", report.msg),
+                "kind": "quickfix",
+                "isPreferred": fix.safe,
+                "edit": { "changes": { uri: edits } },
+            }))
+        });
+        Value::Array(actions.collect())
+    }
+
+    /// The descriptions of the rules reported on the hovered line.
+    fn hover(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+        let line = params["position"]["line"].as_u64().unwrap_or(0) as usize + 1;
+        let reports = self.documents.get(uri).map(|document| &document.reports[..]);
+        let sections: Vec<String> = reports
+            .unwrap_or_default()
+            .iter()
+            .filter(|rule_report| rule_report.report.pos.line == line)
+            .map(|RuleReport { rule, report, .. }| match rules::find_rule(rule) {
+                Some(info) => format!("**{rule}**: {}\n\n{}", info.description, report.msg),
+                None => format!("**{rule}**\n\n{}", report.msg),
+            })
+            .collect();
+        if sections.is_empty() {
+            return Value::Null;
+        }
+        json!({ "contents": { "kind": "markdown", "value": sections.join("\n\n---\n\n") } })
+    }
+
+    fn send(&mut self, message: &Value) -> io::Result<()> {
+        trace!("lsp -> {}", message);
+        let body = message.to_string();
+        write!(self.writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+        self.writer.flush()
+    }
+}
+
+/// Reads one message, `None` at the end of the stream.
+fn read_message(reader: &mut dyn BufRead) -> io::Result<Option<Value>> {
+    loop {
+        let mut content_length: Option<usize> = None;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 {
+                return Ok(None);
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().ok();
+                }
+            }
+        }
+        let content_length = match content_length {
+            Some(content_length) => content_length,
+            None => {
+                error!("LSP message without Content-Length");
+                continue;
+            }
+        };
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        match serde_json::from_slice(&body) {
+            Ok(message) => return Ok(Some(message)),
+            Err(e) => error!("Invalid LSP message: {}", e),
+        }
+    }
+}
+
+fn is_config_file(uri: &str) -> bool {
+    CONFIG_FILE_NAMES.iter().any(|name| uri.ends_with(&format!("/{name}")))
+}
+
+fn uri_to_path(uri: &str) -> PathBuf {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    // percent-decoding, the bytes may form a multi-byte character together
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = tail.get(..2).and_then(|hex| std::str::from_utf8(hex).ok());
+        match escaped.map(|hex| u8::from_str_radix(hex, 16)) {
+            Some(Ok(decoded)) if byte == b'%' => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    let path = String::from_utf8_lossy(&bytes).to_string();
+    // `file:///c%3A/src` on Windows
+    match path.strip_prefix('/') {
+        Some(windows_path) if windows_path.get(1..2) == Some(":") => PathBuf::from(windows_path),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// LSP positions count UTF-16 code units from 0.
+fn offset_to_position(text: &str, offset: usize) -> Value {
+    let offset = offset.min(text.len());
+    let before = &text[..offset];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let character: usize = before[line_start..].chars().map(char::len_utf16).sum();
+    json!({ "line": line, "character": character })
+}
+
+/// The character at `line`:`col` (1-based, in characters), line 0 meaning
+/// the whole file and marked at its start.
+fn report_range(text: &str, line: usize, col: usize) -> Value {
+    let line_text = match line.checked_sub(1).map(|i| text.lines().nth(i)) {
+        Some(Some(line_text)) => line_text,
+        _ => {
+            let start = json!({ "line": line.saturating_sub(1), "character": 0 });
+            return json!({ "start": start, "end": start });
+        }
+    };
+    let mut chars = line_text.chars();
+    let start: usize = chars.by_ref().take(col.saturating_sub(1)).map(char::len_utf16).sum();
+    let end = start + chars.next().map_or(0, char::len_utf16);
+    json!({
+        "start": { "line": line - 1, "character": start },
+        "end": { "line": line - 1, "character": end },
+    })
+}
@@ -1,15 +1,28 @@
 use linked_hash_map::LinkedHashMap;
 
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
+
+use rayon::prelude::*;
 
 use clap::Subcommand;
 use log::{error, trace};
 use lualint::{
+    config::{Config, ConfigLoader},
+    driver::{self, drive, RuleReport},
     lint::{self, Linter},
-    rules::{self, LintReport},
+    rules::{self, LintReport, ReportLevel},
 };
 
+mod baseline;
+mod format;
+mod lsp;
+#[cfg(test)]
 mod tests;
+mod walk;
+
+use baseline::{Baseline, BaselineEntry};
+use format::OutputFormat;
+use walk::normalize_path;
 
 #[derive(clap::Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -20,19 +33,104 @@ pub struct Args {
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
-    Run {
-        /// File to lint
-        filename: String,
-        #[clap(long)]
-        rules: String,
-        // if ignore file is given, errors in these ranges will be ignored
-        #[clap(long)]
-        ignore: Option<String>,
-        // if focus file is given, only errors in these ranges will be reported
-        #[clap(long)]
-        focus: Option<String>,
-    },
-    Rules,
+    Run(RunArgs),
+    /// Record the current reports so that `run --baseline` only shows new ones
+    Baseline(BaselineArgs),
+    /// List the available rules
+    Rules(RulesArgs),
+    /// Start a language server on stdin/stdout
+    Lsp(LspArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RulesArgs {
+    /// Print a JSON Schema of the config file instead, for editor completion
+    #[clap(long)]
+    pub schema: bool,
+}
+
+/// The config of the buffers linted by `lsp`.
+#[derive(clap::Args, Debug)]
+pub struct LspArgs {
+    /// Rules as a json map or a path to a json file, applied on top of the
+    /// `lualint.toml`/`.lualint.json` files found next to and above each file
+    #[clap(long)]
+    pub rules: Option<String>,
+    /// Lua dialect to check against: 5.1, 5.2, 5.3, 5.4 or luajit (default)
+    #[clap(long)]
+    pub lua_version: Option<lint::LuaVersion>,
+}
+
+/// What to lint and how, shared by `run` and `baseline`.
+#[derive(clap::Args, Debug)]
+pub struct LintArgs {
+    /// Files or directories to lint, directories are searched recursively, `-` reads
+    /// the source from stdin
+    #[clap(required = true)]
+    pub paths: Vec<String>,
+    /// Path of the source read from stdin, used in the reports and to find config files
+    #[clap(long, value_name = "PATH")]
+    pub stdin_filename: Option<String>,
+    /// Rules as a json map or a path to a json file, applied on top of the
+    /// `lualint.toml`/`.lualint.json` files found next to and above each file
+    #[clap(long)]
+    pub rules: Option<String>,
+    // if ignore file is given, errors in these ranges will be ignored
+    #[clap(long)]
+    pub ignore: Option<String>,
+    // if focus file is given, only errors in these ranges will be reported
+    #[clap(long)]
+    pub focus: Option<String>,
+    /// Only report on the lines added by a unified diff read from a file or `-` for stdin,
+    /// e.g. the output of `git diff -U0`
+    #[clap(long, value_name = "FILE", conflicts_with_all = ["focus", "since"])]
+    pub diff: Option<String>,
+    /// Only report on the lines changed since a git revision, paths are relative to the
+    /// current directory
+    #[clap(long, value_name = "REV", conflicts_with = "focus")]
+    pub since: Option<String>,
+    /// Lua dialect to check against: 5.1, 5.2, 5.3, 5.4 or luajit (default)
+    #[clap(long)]
+    pub lua_version: Option<lint::LuaVersion>,
+    /// Glob of files to lint inside directories, may be repeated (default: *.lua)
+    #[clap(long)]
+    pub include: Vec<String>,
+    /// Glob of files to skip, may be repeated
+    #[clap(long)]
+    pub exclude: Vec<String>,
+    /// Report `-- lualint:` suppression comments that silence nothing
+    #[clap(long)]
+    pub report_unused_suppressions: bool,
+    /// Number of files linted in parallel (default: number of CPUs)
+    #[clap(long, short)]
+    pub jobs: Option<usize>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RunArgs {
+    #[command(flatten)]
+    pub lint: LintArgs,
+    /// Write fixes made by the rules back to the file
+    #[clap(long)]
+    pub fix: bool,
+    /// Lowest level that makes the run fail: info (default), warning or error
+    #[clap(long, value_name = "LEVEL")]
+    pub fail_on: Option<ReportLevel>,
+    /// Output format, everything but `text` is printed to stdout once all files are linted
+    #[clap(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+    /// Baseline file written by `lualint baseline`, its reports are not shown
+    #[clap(long)]
+    pub baseline: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct BaselineArgs {
+    #[command(flatten)]
+    pub lint: LintArgs,
+    /// File to write the baseline to
+    #[clap(long, short, default_value = baseline::DEFAULT_BASELINE_FILE)]
+    pub output: String,
 }
 
 pub struct SpecificFileRanges {
@@ -72,7 +170,43 @@ impl SpecificRanges {
         spec_ranges
     }
 
+    /// Takes the ranges of the new side of every hunk, so that only the added
+    /// and changed lines are hit. Removed-only hunks and deleted files are skipped.
+    pub fn from_unified_diff(diff: &str) -> Self {
+        let mut spec_ranges = Self::new();
+        let mut file: Option<&str> = None;
+        for line in diff.lines() {
+            if let Some(path) = line.strip_prefix("+++ ") {
+                // `+++ b/path\t2023-01-01 00:00:00` from non-git diffs
+                let path = path.split('\t').next().unwrap_or(path);
+                file = match path {
+                    "/dev/null" => None,
+                    path => Some(path.strip_prefix("b/").unwrap_or(path)),
+                };
+            } else if let (Some(file), Some(hunk)) = (file, line.strip_prefix("@@ ")) {
+                // @@ -start[,count] +start[,count] @@
+                let new_side = hunk.split_whitespace().find_map(|part| part.strip_prefix('+'));
+                let (start, count) = match new_side.map(|range| range.split_once(',')) {
+                    Some(Some((start, count))) => (start.parse(), count.parse()),
+                    Some(None) => (new_side.unwrap().parse(), Ok(1)),
+                    None => continue,
+                };
+                match (start, count) {
+                    (Ok(start), Ok(count)) if count > 0 => {
+                        spec_ranges.add(file, start, start + count - 1)
+                    }
+                    (Ok(_), Ok(_)) => {}
+                    _ => error!("Invalid hunk header: {}", line),
+                }
+            }
+        }
+        spec_ranges.cache();
+        spec_ranges
+    }
+
     pub fn add(&mut self, file: &str, start: usize, end: usize) {
+        let file = normalize_path(file);
+        let file = file.as_str();
         let mut found_file = false;
         for file_range in &mut self.file_ranges {
             if file_range.file == file {
@@ -94,7 +228,7 @@ impl SpecificRanges {
     }
 
     pub fn hit(&self, filename: &str, line: usize) -> bool {
-        if let Some(ranges) = self._cache.get(filename) {
+        if let Some(ranges) = self._cache.get(&normalize_path(filename)) {
             for (start, end) in ranges {
                 if line >= *start && line <= *end {
                     return true;
@@ -107,92 +241,361 @@ impl SpecificRanges {
 
 type RuleName = String;
 type RuleConfig = serde_json::Value;
-// enabled_rules = "[rule_name1:{},rule_name2:{key: value, key: value}]"
-pub fn handle_run_command(
-    filename: &str,
-    enabled_rules: &str,
-    ignore_file_opt: Option<String>,
-    focus_file_opt: Option<String>,
-) {
-    if ignore_file_opt.is_some() && focus_file_opt.is_some() {
-        error!("Cannot use both `--ignore` and `--focus`");
-        return;
+/// How `lint_file` lints and reports a file.
+pub struct LintOptions<'a> {
+    /// write fixes made by the rules back to the file
+    pub fix: bool,
+    pub ignore: Option<&'a SpecificRanges>,
+    pub focus: Option<&'a SpecificRanges>,
+    pub baseline: Option<&'a Baseline>,
+    /// write the text report along with the other messages
+    pub write_text: bool,
+}
+
+/// What `lint_file` found in a file.
+pub struct LintedFile {
+    pub reports: Vec<RuleReport>,
+    /// baseline entries of the file that no report matches anymore
+    pub stale: Vec<BaselineEntry>,
+    /// the fixed source of a buffer read from stdin with `--fix`, printed instead of written
+    pub fixed: Option<String>,
+}
+
+/// Name of the source read from stdin without `--stdin-filename`.
+const STDIN_FILENAME: &str = "stdin.lua";
+
+/// Lints every file selected by `args` in parallel. Returns the file names,
+/// their results (`None` if they could not be read) and their buffered text
+/// output in order, or `None` if the arguments are invalid.
+#[allow(clippy::type_complexity)]
+fn lint_paths(
+    args: &LintArgs,
+    fix: bool,
+    write_text: bool,
+    baseline: Option<&Baseline>,
+) -> Option<Vec<(String, Option<LintedFile>, Vec<u8>)>> {
+    let has_focus = args.focus.is_some() || args.diff.is_some() || args.since.is_some();
+    if args.ignore.is_some() && has_focus {
+        error!("Cannot use `--ignore` with `--focus`, `--diff` or `--since`");
+        return None;
     }
-    let mut linter = match build_config_linter(enabled_rules) {
-        Some(value) => value,
+    let override_config = override_config(args.rules.as_deref(), args.lua_version)?;
+    let ignore = args.ignore.as_deref().map(read_specific_ranges);
+    let focus = if let Some(diff) = &args.diff {
+        Some(SpecificRanges::from_unified_diff(&read_diff(diff)?))
+    } else if let Some(rev) = &args.since {
+        Some(SpecificRanges::from_unified_diff(&git_diff_since(rev)?))
+    } else {
+        args.focus.as_deref().map(read_specific_ranges)
+    };
+    let stdin_src = match args.paths.iter().any(|path| path == "-") {
+        true if args.paths.len() > 1 => {
+            error!("Cannot lint stdin `-` together with other paths");
+            return None;
+        }
+        true if args.diff.as_deref() == Some("-") => {
+            error!("Cannot read both the source and `--diff` from stdin");
+            return None;
+        }
+        true => {
+            let mut src = String::new();
+            if let Err(e) = io::stdin().read_to_string(&mut src) {
+                error!("Error reading stdin: {}", e);
+                return None;
+            }
+            Some(src)
+        }
+        false => None,
+    };
+    let files = match &stdin_src {
+        Some(_) => vec![args.stdin_filename.clone().unwrap_or_else(|| STDIN_FILENAME.to_string())],
+        None => match walk::collect_files(&args.paths, &args.include, &args.exclude) {
+            Ok(files) => files,
+            Err(e) => {
+                error!("{}", e);
+                return None;
+            }
+        },
+    };
+
+    let mut config_loader = ConfigLoader::default();
+    let mut file_configs: Vec<(String, Config)> = vec![];
+    for filename in files {
+        let mut config = match config_loader.load_for_file(std::path::Path::new(&filename)) {
+            Ok(config) => config,
+            Err(e) => {
+                error!("Invalid config file: {}", e);
+                return None;
+            }
+        };
+        config.merge(&override_config);
+        if config.is_excluded(std::path::Path::new(&filename)) {
+            if stdin_src.is_none() {
+                continue;
+            }
+            // still passed through, so that `--fix` prints it unchanged
+            config.rules.clear();
+        }
+        // fail early on unknown rules instead of once per file
+        build_linter(&config)?;
+        file_configs.push((filename, config));
+    }
+
+    let options =
+        LintOptions { fix, ignore: ignore.as_ref(), focus: focus.as_ref(), baseline, write_text };
+    let lint_one = |(filename, config): &(String, Config)| {
+        // files may have different configs, and building a linter is cheap
+        let mut linter = build_linter(config).unwrap();
+        linter.report_unused_suppressions = args.report_unused_suppressions;
+        let mut output = Vec::new();
+        let linted = match &stdin_src {
+            Some(lua_src) => {
+                let (mut linted, fixed) =
+                    lint_buffer(filename, lua_src, &mut linter, &options, &mut output);
+                linted.fixed = Some(fixed).filter(|_| fix);
+                Some(linted)
+            }
+            None => lint_file(filename, &mut linter, &options, &mut output),
+        };
+        (filename.clone(), linted, output)
+    };
+    let mut pool = rayon::ThreadPoolBuilder::new();
+    if let Some(jobs) = args.jobs {
+        pool = pool.num_threads(jobs);
+    }
+    match pool.build() {
+        Ok(pool) => Some(pool.install(|| file_configs.par_iter().map(lint_one).collect())),
+        Err(e) => {
+            error!("Failed to start worker threads: {}", e);
+            Some(file_configs.iter().map(lint_one).collect())
+        }
+    }
+}
+
+/// The config given on the command line, applied on top of the config files.
+fn override_config(rules: Option<&str>, lua_version: Option<lint::LuaVersion>) -> Option<Config> {
+    // enabled_rules = "[rule_name1:{},rule_name2:{key: value, key: value}]"
+    let mut override_config = match rules {
+        Some(enabled_rules) => match parse_enabled_rules(enabled_rules).map(Config::from_rules) {
+            Some(Ok(config)) => config,
+            Some(Err(e)) => {
+                error!("Invalid `--rules`: {}", e);
+                return None;
+            }
+            None => return None,
+        },
+        None => Config::default(),
+    };
+    if lua_version.is_some() {
+        override_config.lua_version = lua_version;
+    }
+    Some(override_config)
+}
+
+pub fn handle_run_command(args: &RunArgs) {
+    let baseline = match args.baseline.as_deref().map(Baseline::from_file) {
+        Some(Ok(baseline)) => Some(baseline),
+        Some(Err(e)) => {
+            error!("Invalid baseline: {}", e);
+            return;
+        }
+        None => None,
+    };
+    let write_text = args.format == OutputFormat::Text;
+    // the fixed source of stdin goes to stdout, everything else to stderr
+    let fix_stdin = args.fix && args.lint.paths.iter().any(|path| path == "-");
+    let results = match lint_paths(&args.lint, args.fix, write_text, baseline.as_ref()) {
+        Some(results) => results,
         None => return,
     };
-    if let Some(ignore_file) = ignore_file_opt {
-        let mut ignore_file_content = String::new();
-        let mut ignore_file = std::fs::File::open(ignore_file).unwrap();
-        ignore_file.read_to_string(&mut ignore_file_content).unwrap();
-        let ignore = SpecificRanges::from_csv(&ignore_file_content);
-        lint_file(filename, &mut linter, true, Some(&ignore), None, &mut io::stdout());
-    } else if let Some(focus_file) = focus_file_opt {
-        let mut focus_file_content = String::new();
-        let mut focus_file = std::fs::File::open(focus_file).unwrap();
-        focus_file.read_to_string(&mut focus_file_content).unwrap();
-        let focus = SpecificRanges::from_csv(&focus_file_content);
-        lint_file(filename, &mut linter, false, None, Some(&focus), &mut io::stdout());
-    } else {
-        lint_file(filename, &mut linter, true, None, None, &mut io::stdout());
+
+    let mut stdout = io::stdout().lock();
+    let mut stderr = io::stderr().lock();
+    let mut fixed_stdin: Option<String> = None;
+    let fail_on = args.fail_on.unwrap_or(ReportLevel::Info);
+    let n_files = results.len();
+    let mut n_problems = 0;
+    let mut failed = false;
+    let mut all_reports: Vec<RuleReport> = vec![];
+    let mut stale: Vec<BaselineEntry> = vec![];
+    let mut linted_files = std::collections::HashSet::new();
+    for (filename, linted, output) in results {
+        // keep stdout parseable for the machine-readable formats
+        if write_text && !fix_stdin {
+            stdout.write_all(&output).unwrap();
+        } else {
+            stderr.write_all(&output).unwrap();
+        }
+        // unreadable files count as errors
+        let worst = match &linted {
+            Some(linted) => linted.reports.iter().map(|r| r.report.level).max(),
+            None => Some(ReportLevel::Error),
+        };
+        if let Some(worst) = worst {
+            n_problems += 1;
+            failed |= worst >= fail_on;
+        }
+        if let Some(linted) = linted {
+            all_reports.extend(linted.reports);
+            stale.extend(linted.stale);
+            fixed_stdin = fixed_stdin.or(linted.fixed);
+        }
+        linted_files.insert(normalize_path(&filename));
+    }
+    if let Some(baseline) = &baseline {
+        // files that are gone can't match anymore, others were just not linted this time
+        stale.extend(
+            baseline
+                .entries
+                .iter()
+                .filter(|entry| {
+                    !linted_files.contains(&entry.file)
+                        && !std::path::Path::new(&entry.file).exists()
+                })
+                .cloned(),
+        );
+    }
+
+    let reports_out: &mut dyn io::Write = if fix_stdin { &mut stderr } else { &mut stdout };
+    if write_text {
+        if n_files > 1 {
+            writeln!(reports_out, "== summary").unwrap();
+            writeln!(reports_out, "{} files linted, {} with problems", n_files, n_problems)
+                .unwrap();
+        }
+    } else if let Err(e) = format::write_reports(args.format, &all_reports, reports_out) {
+        error!("Failed to write reports: {}", e);
+    }
+    if !stale.is_empty() {
+        let out: &mut dyn io::Write =
+            if write_text && !fix_stdin { &mut stdout } else { &mut stderr };
+        writeln!(out, "== baseline").unwrap();
+        writeln!(out, "{} baseline entries no longer match:", stale.len()).unwrap();
+        for entry in &stale {
+            writeln!(out, "{}: {}: {}", entry.file, entry.rule, entry.message).unwrap();
+        }
+    }
+    if let Some(fixed) = fixed_stdin {
+        stdout.write_all(fixed.as_bytes()).unwrap();
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+pub fn handle_baseline_command(args: &BaselineArgs) {
+    let results = match lint_paths(&args.lint, false, false, None) {
+        Some(results) => results,
+        None => return,
+    };
+    let mut stderr = io::stderr().lock();
+    let mut reports: Vec<RuleReport> = vec![];
+    for (_, linted, output) in results {
+        stderr.write_all(&output).unwrap();
+        reports.extend(linted.map(|linted| linted.reports).unwrap_or_default());
+    }
+    let baseline = Baseline::from_reports(&reports);
+    let json = serde_json::to_string_pretty(&baseline.to_json()).unwrap();
+    match std::fs::write(&args.output, json + "\n") {
+        Ok(_) => println!("Wrote {} entries to {}", baseline.entries.len(), args.output),
+        Err(e) => {
+            error!("Error writing {}: {}", args.output, e);
+            std::process::exit(1);
+        }
     }
 }
 
+pub fn handle_lsp_command(args: &LspArgs) {
+    let override_config = match override_config(args.rules.as_deref(), args.lua_version) {
+        Some(override_config) => override_config,
+        None => std::process::exit(1),
+    };
+    let mut stdout = io::stdout().lock();
+    let mut server = lsp::Server::new(&mut stdout, override_config);
+    if let Err(e) = server.serve(&mut io::stdin().lock()) {
+        error!("Language server stopped: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn read_diff(filename: &str) -> Option<String> {
+    let mut diff = String::new();
+    let read = match filename {
+        "-" => io::stdin().read_to_string(&mut diff),
+        _ => std::fs::File::open(filename).and_then(|mut file| file.read_to_string(&mut diff)),
+    };
+    match read {
+        Ok(_) => Some(diff),
+        Err(e) => {
+            error!("Error reading diff {}: {}", filename, e);
+            None
+        }
+    }
+}
+
+fn git_diff_since(rev: &str) -> Option<String> {
+    // `--relative` makes the paths relative to the current directory like the linted ones
+    let output = std::process::Command::new("git")
+        .args(["diff", "-U0", "--no-color", "--no-ext-diff", "--relative", rev, "--"])
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            Some(String::from_utf8_lossy(&output.stdout).to_string())
+        }
+        Ok(output) => {
+            error!("`git diff {}` failed: {}", rev, String::from_utf8_lossy(&output.stderr).trim());
+            None
+        }
+        Err(e) => {
+            error!("Failed to run git: {}", e);
+            None
+        }
+    }
+}
+
+fn read_specific_ranges(filename: &str) -> SpecificRanges {
+    let mut content = String::new();
+    let mut file = std::fs::File::open(filename).unwrap();
+    file.read_to_string(&mut content).unwrap();
+    SpecificRanges::from_csv(&content)
+}
+
+#[cfg(test)]
 fn build_config_linter(enabled_rules: &str) -> Option<Linter> {
+    match Config::from_rules(parse_enabled_rules(enabled_rules)?) {
+        Ok(config) => build_linter(&config),
+        Err(e) => {
+            error!("Invalid `--rules`: {}", e);
+            None
+        }
+    }
+}
+
+fn parse_enabled_rules(enabled_rules: &str) -> Option<Vec<(RuleName, RuleConfig)>> {
     let mut enabled_rules_vec_result = parse_rule_json_file(enabled_rules);
     if enabled_rules_vec_result.is_err() {
         enabled_rules_vec_result = parse_rules_json(enabled_rules);
     }
 
-    let enabled_rules_vec = match enabled_rules_vec_result {
-        Ok(enabled_rules) => enabled_rules,
+    match enabled_rules_vec_result {
+        Ok(enabled_rules) => Some(enabled_rules),
         Err(e) => {
             error!("Failed to parse enabled rules: {}. Value for param `--rules` msut be a valid json map. For example: {}", e, 
             r#"{ "rule_name1": {}, "rule_name2": { "key": "value" } }"#);
             trace!("given value: {}", enabled_rules);
-            return None;
+            None
         }
-    };
+    }
+}
 
-    let mut linter_builder = lint::LinterBuilder::default();
-    for (rule_name, rule_config) in enabled_rules_vec {
-        match rule_name.as_str() {
-            "eof_blank_line" => {
-                linter_builder = linter_builder
-                    .with_rule::<rules::eof_blank_line::EofBlankLine>(&rule_name, &rule_config);
-            }
-            "func_separation" => {
-                linter_builder = linter_builder
-                    .with_rule::<rules::func_separation::FuncSeparation>(&rule_name, &rule_config);
-            }
-            "max_column_width" => {
-                linter_builder = linter_builder
-                    .with_rule::<rules::max_column_width::MaxColumnWidth>(&rule_name, &rule_config);
-            }
-            // "no_trailing_semicolon" => {
-            //     linter_builder = linter_builder
-            //         .with_rule::<rules::no_trailing_semicolon::NoTrailingSemicolon>(&rule_name, &rule_config);
-            // },
-            "one_line_before_else" => {
-                linter_builder = linter_builder
-                    .with_rule::<rules::one_line_before_else::OneLineBeforeElse>(
-                        &rule_name,
-                        &rule_config,
-                    );
-            }
-            "table_ctor_comma" => {
-                linter_builder = linter_builder
-                    .with_rule::<rules::table_ctor_comma::TableCtorComma>(&rule_name, &rule_config);
-            }
-            _ => {
-                error!("Unknown rule: {}", rule_name);
-                return None;
-            }
+fn build_linter(config: &Config) -> Option<Linter> {
+    match driver::build_linter(config) {
+        Ok(linter) => Some(linter),
+        Err(e) => {
+            error!("{}", e);
+            None
         }
     }
-    let linter = linter_builder.build();
-    Some(linter)
 }
 
 fn parse_rule_json_file(
@@ -221,7 +624,11 @@ fn parse_rules_json(
     Ok(enabled_rules)
 }
 
-pub fn print_rules() {
+pub fn print_rules(args: &RulesArgs) {
+    if args.schema {
+        println!("{:#}", lualint::config::json_schema());
+        return;
+    }
     // "name" "description" "version" "config_example"
     println!(
         "{:22} {:10} {:56} {:18}",
@@ -241,64 +648,91 @@ pub fn print_rules() {
     });
 }
 
+/// Lints a file and returns its reports, or `None` if it could not be read.
+/// The text report is written to `writer` along with the other messages.
 pub fn lint_file(
     filename: &str,
     linter: &mut Linter,
-    write_back: bool,
-    ignore: Option<&SpecificRanges>,
-    focus: Option<&SpecificRanges>,
+    options: &LintOptions,
     writer: &mut dyn io::Write,
-) {
+) -> Option<LintedFile> {
     let is_file_existing = std::path::Path::new(filename).exists();
     if !is_file_existing {
         // println!("File not found: {filename}");
         writeln!(writer, "File not found: {}", filename).unwrap();
-        return;
+        return None;
     }
 
     let is_lua_file = filename.ends_with(".lua");
     if !is_lua_file {
         // println!("File is not a lua file: {filename}");
         writeln!(writer, "File is not a lua file: {}", filename).unwrap();
-        return;
+        return Some(LintedFile { reports: vec![], stale: vec![], fixed: None });
     }
-    let _exit_on_err = true; // TODO: make this configurable
-    let processed = match std::fs::read_to_string(filename) {
-        Ok(lua_src) => {
-            let out = drive(&lua_src, linter);
-            let ok = print_lint_report(filename, Some(&lua_src), linter, ignore, focus, writer);
-            if !ok && _exit_on_err {
-                std::process::exit(1);
-            }
-            out
-        }
+    let lua_src = match std::fs::read_to_string(filename) {
+        Ok(lua_src) => lua_src,
         Err(e) => {
             // println!("Error reading file: {e}");
             writeln!(writer, "Error reading file: {}", e).unwrap();
-            return;
+            return None;
         }
     };
+    let (linted, processed) = lint_buffer(filename, &lua_src, linter, options, writer);
 
-    if write_back {
+    // only touch the file when a fix actually changed something
+    if options.fix && processed != lua_src {
         match std::fs::write(filename, processed) {
             Ok(_) => writeln!(writer, "Wrote file: {}", filename).unwrap(),
             Err(e) => writeln!(writer, "Error writing file: {}", e).unwrap(),
         }
-    } else {
-        // info!("Linted: {}", processed);
     }
+
+    Some(linted)
 }
 
-fn format_report(filename: &str, file_content_opt: Option<&str>, report: &LintReport) -> String {
-    let file_contents;
-    if file_content_opt.is_none() {
-        file_contents = std::fs::read_to_string(filename).unwrap();
-    } else {
-        file_contents = file_content_opt.unwrap().to_string();
+/// Lints `lua_src`, which need not be saved in `filename`, and returns its
+/// reports and the fixed source. Nothing is written to disk.
+fn lint_buffer(
+    filename: &str,
+    lua_src: &str,
+    linter: &mut Linter,
+    options: &LintOptions,
+    writer: &mut dyn io::Write,
+) -> (LintedFile, String) {
+    drive(lua_src, linter);
+    let mut rule_reports =
+        collect_reports(filename, lua_src, linter, options.ignore, options.focus);
+    // suppressed and ignored reports are gone already, so are their fixes
+    let (processed, fix_counts) = driver::apply_fixes(lua_src, &rule_reports);
+    let stale = match options.baseline {
+        Some(baseline) => baseline.filter(filename, &mut rule_reports),
+        None => vec![],
+    };
+    if options.write_text {
+        write_text_report(filename, Some(lua_src), &rule_reports, writer);
     }
+    if options.fix && processed != lua_src {
+        print_fix_report(&fix_counts, writer);
+    }
+    (LintedFile { reports: rule_reports, stale, fixed: None }, processed)
+}
+
+fn print_fix_report(fix_counts: &LinkedHashMap<String, usize>, writer: &mut dyn io::Write) {
+    writeln!(writer, "== fix report").unwrap();
+    fix_counts.iter().for_each(|(name, count)| {
+        writeln!(writer, "[fix] {name}: {count}").unwrap();
+    });
+}
+
+fn format_report(filename: &str, file_content_opt: Option<&str>, report: &LintReport) -> String {
+    let file_contents = match file_content_opt {
+        Some(content) => content.to_string(),
+        None => std::fs::read_to_string(filename).unwrap(),
+    };
     let lines: Vec<&str> = file_contents.lines().collect();
-    if report.pos.line == 0 {
-        return format!("{}: {}", filename, report.msg);
+    // line 0 means the whole file, past the last line means the end of it
+    if report.pos.line == 0 || report.pos.line > lines.len() {
+        return format!("{}: {}: {}", filename, report.level, report.msg);
     }
     let line = (report.pos.line, lines[report.pos.line - 1]);
     let continued_line = if report.pos.line < lines.len() {
@@ -323,7 +757,7 @@ fn format_report(filename: &str, file_content_opt: Option<&str>, report: &LintRe
         let lineno = report.pos.line;
         let colno = report.pos.col - 1;
         let path = filename;
-        let message = &report.msg;
+        let message = format!("{}: {}", report.level, report.msg);
         let line = line.1;
 
         fn underline(colno: usize, line: &str) -> String {
@@ -418,58 +852,66 @@ fn format_report(filename: &str, file_content_opt: Option<&str>, report: &LintRe
     format_impl(filename, report, line, continued_line)
 }
 
-fn print_lint_report(
+/// Lints nothing: picks the reports of the last `drive` of `lua_src` that
+/// should be shown, see `driver::collect_reports`, outside of `ignore` and inside `focus`.
+fn collect_reports(
     filename: &str,
-    file_content: Option<&str>,
+    lua_src: &str,
     linter: &mut Linter,
     ignore: Option<&SpecificRanges>,
     focus: Option<&SpecificRanges>,
+) -> Vec<RuleReport> {
+    // suppressions are checked first so that those inside ignored ranges still count as used
+    let mut rule_reports = driver::collect_reports(filename, lua_src, linter);
+    rule_reports.retain(|RuleReport { report, .. }| {
+        let ignored = ignore.is_some_and(|ignore| ignore.hit(filename, report.pos.line));
+        let focused = focus.is_none_or(|focus| focus.hit(filename, report.pos.line));
+        !ignored && focused
+    });
+    rule_reports
+}
+
+fn write_text_report(
+    filename: &str,
+    file_content: Option<&str>,
+    rule_reports: &[RuleReport],
     writer: &mut dyn io::Write,
-) -> bool {
+) {
     let mut report_str = String::new();
-    linter.rule_registry.rule_ctx.iter().for_each(|(name, rule)| {
-        let mut rule_report_str = String::new();
-        rule.get_reports().iter().for_each(|report: &LintReport| {
-            let mut report_tmp: LintReport = report.clone();
-            report_tmp.pos.file = filename.to_string();
-            if let Some(ignore) = ignore {
-                if ignore.hit(filename, report.pos.line) {
-                    return ();
-                }
-            }
-            if let Some(focus) = focus {
-                if !focus.hit(filename, report.pos.line) {
-                    return ();
-                }
-            }
-            rule_report_str
-                .push_str(&format!("{}\n", format_report(filename, file_content, &report_tmp)));
-        });
-        if !rule_report_str.is_empty() {
-            report_str.push_str(&format!("[rule] {name}:\n{rule_report_str}"));
+    let mut last_rule: Option<&str> = None;
+    for RuleReport { rule: name, report, .. } in rule_reports {
+        if last_rule != Some(name.as_str()) {
+            report_str.push_str(&format!("[rule] {name}:\n"));
+            last_rule = Some(name);
         }
-    });
+        report_str.push_str(&format!("{}\n", format_report(filename, file_content, report)));
+    }
     if report_str.is_empty() {
         writeln!(writer, "== lint report").unwrap();
         writeln!(writer, "ok").unwrap();
-        true
     } else {
         writeln!(writer, "== lint report").unwrap();
         writeln!(writer, "{report_str}", report_str = report_str).unwrap();
-        false
     }
 }
 
-pub fn drive(lua_src: &str, linter: &mut Linter) -> String {
-    let lua_src = linter.rule_registry.trigger_preprocess(lua_src);
-    let tokens = full_moon::tokenizer::tokens(lua_src.as_str()).unwrap();
-    let tokens = lint::lint_tokens(&tokens, linter);
-    let input_ast = full_moon::ast::Ast::from_tokens(tokens).unwrap();
-
-    let (_formatted_ast, _ctx) = lint::lint_visitor::lint_ast(&input_ast, linter);
-    // should return stringified _formatted_ast
-    //   but currently we don't provide a way to do that
-    lua_src
+/// Prints the text report of the last `drive` and returns the highest level in it.
+#[cfg(test)]
+fn print_lint_report(
+    filename: &str,
+    file_content: Option<&str>,
+    linter: &mut Linter,
+    ignore: Option<&SpecificRanges>,
+    focus: Option<&SpecificRanges>,
+    writer: &mut dyn io::Write,
+) -> Option<ReportLevel> {
+    let lua_src = match file_content {
+        Some(content) => content.to_string(),
+        None => std::fs::read_to_string(filename).unwrap(),
+    };
+    let rule_reports = collect_reports(filename, &lua_src, linter, ignore, focus);
+    write_text_report(filename, file_content, &rule_reports, writer);
+    rule_reports.iter().map(|rule_report| rule_report.report.level).max()
 }
 
 /// Takes a string of jsonc content and returns a comment free version
@@ -1,9 +1,8 @@
-
+use super::build_config_linter;
 
 // test read rules from file
 #[test]
 fn test_read_rules_from_file() {
-    use super::{build_config_linter};
     let enabled_rules = "scripts/all_rules.jsonc";
     let out = build_config_linter(enabled_rules);
     assert!(out.is_some());
@@ -13,7 +12,6 @@ fn test_read_rules_from_file() {
 
 #[test]
 fn test_basic_line() {
-    use super::{build_config_linter};
     use crate::cli::{drive, print_lint_report};
 
     let source_file_name = "tests/comp/longline.lua";
@@ -30,18 +28,18 @@ fn test_basic_line() {
         Ok(value) => value,
         Err(_) => return,
     };
-    let _ = drive(&source, &mut linter);
-    let _ = print_lint_report(&source_file_name, None, &mut linter, None, None, &mut stdout);
+    drive(&source, &mut linter);
+    let _ = print_lint_report(source_file_name, None, &mut linter, None, None, &mut stdout);
     assert_eq!(String::from_utf8(stdout).unwrap(), r#"== lint report
 [rule] eof_blank_line:
-tests/comp/longline.lua: File is expected to end with a blank line, but does not
+tests/comp/longline.lua: warning: File is expected to end with a blank line, but does not
 [rule] max_column_width:
   --> tests/comp/longline.lua:20:87
    |
 20 | b = {                                                                                  }
    |                                                                                       ^
    |
-   = Line is expected to be at most 80 characters, but is more than 88 characters
+   = warning: Line is expected to be at most 80 characters, but is more than 88 characters
 
 "#);
   }
@@ -49,7 +47,6 @@ tests/comp/longline.lua: File is expected to end with a blank line, but does not
 // test ignore lines
 #[test]
 fn test_ignore_lines() {
-    use super::{build_config_linter};
     use crate::cli::{SpecificRanges, drive, print_lint_report};
 
     let ignore_range = r"test_ignore_lines.txt,1,2
@@ -68,8 +65,8 @@ line5 = 'aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa
         None => return,
     };
     let mut stdout = Vec::new();
-    let _ = drive(&lua_src, &mut linter);
-    let _ = print_lint_report(filename,Some(&lua_src), &mut linter, Some(&ignore), None, &mut stdout);
+    drive(lua_src, &mut linter);
+    let _ = print_lint_report(filename,Some(lua_src), &mut linter, Some(&ignore), None, &mut stdout);
     assert_eq!(String::from_utf8(stdout).unwrap(), r#"== lint report
 [rule] max_column_width:
  --> test_ignore_lines.txt:3:86
@@ -78,13 +75,1162 @@ line5 = 'aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa
 4 | line4 = 'aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa'
   |                                                                                      ^
   |
-  = Line is expected to be at most 80 characters, but is more than 87 characters
+  = warning: Line is expected to be at most 80 characters, but is more than 87 characters
  --> test_ignore_lines.txt:5:86
   |
 5 | line5 = 'aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa'
   |                                                                                      ^
   |
-  = Line is expected to be at most 80 characters, but is more than 87 characters
+  = warning: Line is expected to be at most 80 characters, but is more than 87 characters
+
+"#);
+}
+
+#[test]
+fn test_fix_rewrites_source() {
+    use crate::cli::drive;
+    use lualint::driver::{apply_fixes, collect_reports};
+
+    let enabled_rules = r#"{"table_ctor_comma": {}, "eof_blank_line": {}}"#;
+    let lua_src = "local t = {\n    a = 1 -- keep me\n}";
+    let mut linter = build_config_linter(enabled_rules).unwrap();
+    drive(lua_src, &mut linter);
+    let (fixed, fix_counts) = apply_fixes(lua_src, &collect_reports("", lua_src, &mut linter));
+    assert_eq!(fixed, "local t = {\n    a = 1, -- keep me\n}\n");
+    assert_eq!(fix_counts.get("table_ctor_comma"), Some(&1));
+    assert_eq!(fix_counts.get("eof_blank_line"), Some(&1));
+}
+
+#[test]
+fn test_fix_keeps_clean_source() {
+    use crate::cli::drive;
+    use lualint::driver::{apply_fixes, collect_reports};
+
+    let enabled_rules = "scripts/all_rules.jsonc";
+    let lua_src = "local t = {\n    a = 1,\n}\n\nif a then\n    b()\n\nelse\n    c()\nend\n";
+    let mut linter = build_config_linter(enabled_rules).unwrap();
+    drive(lua_src, &mut linter);
+    let (fixed, fix_counts) = apply_fixes(lua_src, &collect_reports("", lua_src, &mut linter));
+    assert_eq!(fixed, lua_src);
+    assert!(fix_counts.is_empty());
+}
+
+#[test]
+fn test_report_fix_edits() {
+    use crate::cli::drive;
+    use lualint::rules::{self, TextEdit};
+
+    let enabled_rules = r#"{"table_ctor_comma": {}, "eof_blank_line": {}, "func_separation": {}}"#;
+    let lua_src = "local t = {\n    a = 1\n}\nfunction f()\nend -- f\nfunction g()\nend";
+    let mut linter = build_config_linter(enabled_rules).unwrap();
+    drive(lua_src, &mut linter);
+    let mut edits: Vec<TextEdit> = vec![];
+    linter.rule_registry.get_all_ctx().iter().for_each(|(_, rule)| {
+        rule.get_reports().iter().for_each(|report| {
+            let fix = report.fix.clone().unwrap();
+            assert!(fix.safe);
+            edits.extend(fix.edits);
+        });
+    });
+    assert_eq!(
+        rules::apply_edits(lua_src, &edits),
+        "local t = {\n    a = 1,\n}\nfunction f()\nend -- f\n\n\nfunction g()\nend\n"
+    );
+}
+
+#[test]
+fn test_table_ctor_comma_after_brackets() {
+    use lualint::rules::table_ctor_comma::TableCtorComma;
+
+    let lua_src = "local t = {\n    a = f(1)\n}\nlocal u = {\n    x[1]\n}\n";
+    let config = serde_json::json!({});
+    let (reports, fixed) = lint_rule::<TableCtorComma>("table_ctor_comma", config, lua_src);
+    assert_eq!(reports.len(), 2);
+    assert_eq!(fixed, "local t = {\n    a = f(1),\n}\nlocal u = {\n    x[1],\n}\n");
+}
+
+#[test]
+fn test_no_trailing_space_fix_edits() {
+    use lualint::lint::LinterBuilder;
+    use lualint::rules::{self, no_trailing_space::NoTrailingWhitespace};
+    use crate::cli::drive;
+
+    let lua_src = "local a = 1  \nlocal s = [[x  \n]] -- c  \nlocal b = 2\t";
+    let mut linter = LinterBuilder::default()
+        .with_rule::<NoTrailingWhitespace>("no_trailing_space", &serde_json::Value::Null)
+        .build();
+    drive(lua_src, &mut linter);
+    let reports = linter.rule_registry.get_all_ctx().get("no_trailing_space").unwrap().get_reports();
+    assert_eq!(reports.len(), 2);
+    assert_eq!((reports[0].pos.line, reports[0].pos.col), (1, 12));
+    let edits: Vec<_> = reports.iter().flat_map(|r| r.fix.clone().unwrap().edits).collect();
+    assert_eq!(
+        rules::apply_edits(lua_src, &edits),
+        "local a = 1\nlocal s = [[x  \n]] -- c  \nlocal b = 2"
+    );
+}
+
+#[test]
+fn test_syntax_error_report() {
+    use crate::cli::{drive, print_lint_report};
+
+    let filename = "test_syntax_error.lua";
+    let enabled_rules = r#"{"table_ctor_comma": {}}"#;
+    let lua_src = "local t = {\n    a = 1,\n}\nlocal = 2\n";
+    let mut linter = build_config_linter(enabled_rules).unwrap();
+    let mut stdout = Vec::new();
+    drive(lua_src, &mut linter);
+    let worst = print_lint_report(filename, Some(lua_src), &mut linter, None, None, &mut stdout);
+    assert_eq!(worst, Some(lualint::rules::ReportLevel::Error));
+    assert_eq!(String::from_utf8(stdout).unwrap(), r#"== lint report
+[rule] syntax_error:
+ --> test_syntax_error.lua:4:6
+  |
+4 | local = 2
+  |      ^
+  |
+  = error: Syntax error: unexpected token `=`, expected name
 
 "#);
 }
+
+#[test]
+fn test_tokenizer_error_report() {
+    use crate::cli::drive;
+    use lualint::rules::ReportLevel;
+
+    let lua_src = "local s = \"unclosed\n";
+    let mut linter = build_config_linter(r#"{"eof_blank_line": {}}"#).unwrap();
+    drive(lua_src, &mut linter);
+    let reports = linter.rule_registry.get_all_ctx().get("syntax_error").unwrap().get_reports();
+    assert_eq!(reports.len(), 1);
+    assert!(matches!(reports[0].level, ReportLevel::Error));
+    assert_eq!(reports[0].pos.line, 1);
+}
+
+#[test]
+fn test_nested_statements_are_visited() {
+    use lualint::driver::lint_with;
+
+    let enabled_rules = r#"{"table_ctor_comma": {}}"#;
+    let lua_src = r#"x.y = {
+    a = 1
+}
+do
+    local t = {
+        b = 2
+    }
+end
+f({
+    c = 3
+}):m(function()
+    return {
+        d = 4
+    }
+end)
+for _, v in pairs({
+    e = 5
+}) do
+    g[{
+        f = 6
+    }] = v
+end
+"#;
+    let mut linter = build_config_linter(enabled_rules).unwrap();
+    let result = lint_with(&mut linter, "a.lua", lua_src);
+    let lines: Vec<usize> = result.reports.iter().map(|r| r.report.pos.line).collect();
+    assert_eq!(lines, vec![2, 6, 10, 13, 17, 20]);
+    assert_eq!(result.fixed.unwrap().matches(",\n").count(), 6);
+}
+
+#[test]
+fn test_unsupported_syntax_by_lua_version() {
+    use crate::cli::drive;
+    use lualint::lint::LuaVersion;
+
+    let lua_src = "local a <const> = 7 // 2 | 1\nfor i = 1, 2 do\n    goto continue\n    ::continue::\nend\n";
+    let messages = |lua_version: LuaVersion| -> Vec<String> {
+        let mut linter = build_config_linter(r#"{"eof_blank_line": {}}"#).unwrap();
+        linter.lua_version = lua_version;
+        drive(lua_src, &mut linter);
+        match linter.rule_registry.get_all_ctx().get("syntax_error") {
+            Some(ctx) => ctx
+                .get_reports()
+                .iter()
+                .map(|report| format!("{}:{} {}", report.pos.line, report.pos.col, report.msg))
+                .collect(),
+            None => vec![],
+        }
+    };
+
+    assert_eq!(messages(LuaVersion::Lua54), Vec::<String>::new());
+    assert_eq!(messages(LuaVersion::Lua53), vec![
+        "1:9 Syntax error: variable attributes are not supported by Lua 5.3",
+    ]);
+    assert_eq!(messages(LuaVersion::LuaJIT), vec![
+        "1:9 Syntax error: variable attributes are not supported by LuaJIT",
+        "1:26 Syntax error: bitwise operators are not supported by LuaJIT",
+        "1:21 Syntax error: the `//` operator is not supported by LuaJIT",
+    ]);
+    assert_eq!(messages(LuaVersion::Lua51).len(), 5);
+}
+
+#[test]
+fn test_lua_version_config_key() {
+    use lualint::lint::LuaVersion;
+
+    let linter = build_config_linter(r#"{"lua_version": "5.1", "eof_blank_line": {}}"#).unwrap();
+    assert_eq!(linter.lua_version, LuaVersion::Lua51);
+    assert!(build_config_linter(r#"{"lua_version": "5.0"}"#).is_none());
+}
+
+#[test]
+fn test_collect_files_from_dirs() {
+    use crate::cli::walk::collect_files;
+
+    let root = std::env::temp_dir().join(format!("lualint_walk_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    for dir in ["sub", "ignored", "spec", ".hidden"] {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    for file in ["a.lua", "sub/b.lua", "sub/c.txt", "ignored/d.lua", "spec/e.lua", ".hidden/f.lua"] {
+        std::fs::write(root.join(file), "").unwrap();
+    }
+    std::fs::write(root.join(".gitignore"), "ignored/\n").unwrap();
+    let root_str = root.to_string_lossy().to_string();
+    let rel = |files: Vec<String>| -> Vec<String> {
+        files.iter().map(|f| f.strip_prefix(&root_str).unwrap().to_string()).collect()
+    };
+
+    let files =
+        collect_files(std::slice::from_ref(&root_str), &[], &["**/spec/**".to_string()]).unwrap();
+    assert_eq!(rel(files), vec!["/a.lua", "/sub/b.lua"]);
+
+    let files =
+        collect_files(std::slice::from_ref(&root_str), &["*.txt".to_string()], &[]).unwrap();
+    assert_eq!(rel(files), vec!["/sub/c.txt"]);
+
+    // explicit files are kept even when ignored, and only once
+    let explicit = root.join("ignored/d.lua").to_string_lossy().to_string();
+    let files = collect_files(&[explicit.clone(), explicit.clone()], &[], &[]).unwrap();
+    assert_eq!(files, vec![explicit]);
+
+    assert!(collect_files(&[root_str], &["[".to_string()], &[]).is_err());
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_config_file_parsing() {
+    use lualint::{config::Config, lint::LuaVersion};
+    use std::path::Path;
+
+    let config = Config::from_toml(
+        r#"
+        lua_version = "5.1"
+        exclude = ["spec/**"]
+
+        [rules]
+        eof_blank_line = true
+        func_separation = false
+
+        [rules.max_column_width]
+        max_col = 100
+        "#,
+        Path::new("proj"),
+    )
+    .unwrap();
+    assert_eq!(config.lua_version, Some(LuaVersion::Lua51));
+    let enabled: Vec<&String> = config.enabled_rules().map(|(name, _)| name).collect();
+    assert_eq!(enabled, vec!["eof_blank_line", "max_column_width"]);
+    assert!(config.is_excluded(Path::new("proj/spec/a.lua")));
+    assert!(!config.is_excluded(Path::new("proj/src/a.lua")));
+
+    let json = Config::from_json(r#"{"rules": {"eof_blank_line": {}}}"#, Path::new(".")).unwrap();
+    assert_eq!(json.enabled_rules().count(), 1);
+
+    assert!(Config::from_toml("unknown = 1", Path::new(".")).is_err());
+    assert!(Config::from_toml("[rules]\neof_blank_line = 1", Path::new(".")).is_err());
+    assert!(Config::from_toml("lua_version = \"5.0\"", Path::new(".")).is_err());
+}
+
+#[test]
+fn test_config_file_discovery() {
+    use lualint::config::ConfigLoader;
+
+    let root = std::env::temp_dir().join(format!("lualint_config_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("proj/sub/deep")).unwrap();
+    std::fs::write(root.join("lualint.toml"), "[rules]\none_line_before_else = true\n").unwrap();
+    std::fs::write(
+        root.join("proj/lualint.toml"),
+        "root = true\n[rules]\neof_blank_line = true\nfunc_separation = true\n\
+         [rules.max_column_width]\nmax_col = 100\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("proj/sub/.lualint.json"),
+        r#"{"lua_version": "5.4", "rules": {"func_separation": false, "max_column_width": {"x": 1}}}"#,
+    )
+    .unwrap();
+
+    let mut loader = ConfigLoader::default();
+    let config = loader.load_for_file(&root.join("proj/sub/deep/a.lua")).unwrap();
+    let enabled: Vec<(&String, String)> =
+        config.enabled_rules().map(|(name, options)| (name, options.to_string())).collect();
+    // the outer config is cut off by `root = true`, the closest config wins
+    assert_eq!(
+        enabled,
+        vec![
+            (&"eof_blank_line".to_string(), "{}".to_string()),
+            (&"max_column_width".to_string(), r#"{"max_col":100,"x":1}"#.to_string()),
+        ]
+    );
+    assert_eq!(config.lua_version, Some(lualint::lint::LuaVersion::Lua54));
+
+    let config = loader.load_for_file(&root.join("a.lua")).unwrap();
+    assert_eq!(config.enabled_rules().count(), 1);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_suppression_comments() {
+    use crate::cli::{drive, print_lint_report};
+
+    let lua_src = r#"-- lualint: disable-next-line max_column_width
+line2 = 'aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa'
+line3 = 'aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa'
+-- lualint: disable-next-line table_ctor_comma
+line5 = 1
+"#;
+    let mut linter = build_config_linter(r#"{"max_column_width": {}}"#).unwrap();
+    linter.report_unused_suppressions = true;
+    let mut stdout = Vec::new();
+    drive(lua_src, &mut linter);
+    let _ = print_lint_report("test.lua", Some(lua_src), &mut linter, None, None, &mut stdout);
+    let out = String::from_utf8(stdout).unwrap();
+    assert!(!out.contains("test.lua:2:"));
+    assert!(out.contains("test.lua:3:"));
+    assert!(out.contains("[rule] unused_suppression:\n --> test.lua:4:0"));
+    assert!(out.contains("Unused suppression of `table_ctor_comma`"));
+}
+
+#[test]
+fn test_fix_skips_suppressed_lines() {
+    use super::{lint_buffer, LintOptions};
+    use lualint::driver::lint_with;
+
+    let lua_src = "-- lualint: disable table_ctor_comma
+local t = {
+    a = 1
+}
+-- lualint: enable table_ctor_comma
+local u = {
+    b = 2
+}
+";
+    let expected = lua_src.replace("b = 2", "b = 2,");
+    let mut linter = build_config_linter(r#"{"table_ctor_comma": {}}"#).unwrap();
+    let options =
+        LintOptions { fix: true, ignore: None, focus: None, baseline: None, write_text: true };
+    let mut output = Vec::new();
+    let (linted, fixed) = lint_buffer("a.lua", lua_src, &mut linter, &options, &mut output);
+    assert_eq!(fixed, expected);
+    assert_eq!(linted.reports.len(), 1);
+    assert!(String::from_utf8(output).unwrap().contains("[fix] table_ctor_comma: 1"));
+
+    let result = lint_with(&mut linter, "a.lua", lua_src);
+    assert_eq!(result.fixed.as_deref(), Some(expected.as_str()));
+    let result = lint_with(&mut linter, "a.lua", &format!("-- lualint: disable\n{lua_src}"));
+    assert!(result.reports.is_empty());
+    assert_eq!(result.fixed, None);
+}
+
+#[test]
+fn test_fix_applies_text_edits() {
+    use super::{lint_buffer, LintOptions};
+
+    // both rules only describe their fixes as text edits
+    let lua_src = "local x = 1   \nfunction a()\nend\nfunction b()\nend\n";
+    let mut linter =
+        build_config_linter(r#"{"no_trailing_space": {}, "func_separation": {}}"#).unwrap();
+    let options =
+        LintOptions { fix: true, ignore: None, focus: None, baseline: None, write_text: true };
+    let mut output = Vec::new();
+    let (_, fixed) = lint_buffer("a.lua", lua_src, &mut linter, &options, &mut output);
+    assert_eq!(fixed, "local x = 1\nfunction a()\nend\n\n\nfunction b()\nend\n");
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("[fix] no_trailing_space: 1\n[fix] func_separation: 1"), "{output}");
+}
+
+#[test]
+fn test_rule_severity() {
+    use crate::cli::{drive, print_lint_report};
+    use lualint::rules::ReportLevel;
+
+    let lua_src = "local t = {\n    a = 1\n}";
+    let lint = |enabled_rules: &str| -> (Option<ReportLevel>, String) {
+        let mut linter = build_config_linter(enabled_rules).unwrap();
+        let mut stdout = Vec::new();
+        drive(lua_src, &mut linter);
+        let worst =
+            print_lint_report("test.lua", Some(lua_src), &mut linter, None, None, &mut stdout);
+        (worst, String::from_utf8(stdout).unwrap())
+    };
+
+    let (worst, out) = lint(r#"{"eof_blank_line": {}, "table_ctor_comma": {}}"#);
+    assert_eq!(worst, Some(ReportLevel::Warning));
+    assert!(out.contains("test.lua: warning: File is expected to end with a blank line"));
+
+    let (worst, out) = lint(
+        r#"{"eof_blank_line": {"severity": "info"}, "table_ctor_comma": {"severity": "error"}}"#,
+    );
+    assert_eq!(worst, Some(ReportLevel::Error));
+    assert!(out.contains("test.lua: info: File is expected to end with a blank line"));
+    assert!(out.contains("= error: "));
+
+    let (worst, out) = lint(r#"{"eof_blank_line": {"severity": "off"}}"#);
+    assert_eq!(worst, None);
+    assert!(out.ends_with("ok\n"));
+
+    assert!(build_config_linter(r#"{"eof_blank_line": {"severity": "fatal"}}"#).is_none());
+}
+
+#[test]
+fn test_output_formats() {
+    use super::{collect_reports, drive};
+    use crate::cli::format::{write_reports, OutputFormat};
+
+    let lua_src = "local t = {\n    a = \"<&>\"\n}";
+    let mut linter =
+        build_config_linter(r#"{"eof_blank_line": {}, "table_ctor_comma": {"severity": "error"}}"#)
+            .unwrap();
+    drive(lua_src, &mut linter);
+    let reports = collect_reports("a.lua", lua_src, &mut linter, None, None);
+    let write = |format: OutputFormat| -> String {
+        let mut out = Vec::new();
+        write_reports(format, &reports, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    assert_eq!(
+        write(OutputFormat::Compact),
+        "a.lua:1:1: warning: eof_blank_line: File is expected to end with a blank line, but does not\n\
+         a.lua:2:14: error: table_ctor_comma: Table constructor should have a comma after the last field.\n"
+    );
+
+    let json: serde_json::Value = serde_json::from_str(&write(OutputFormat::Json)).unwrap();
+    assert_eq!(json[1]["rule"], "table_ctor_comma");
+    assert_eq!(json[1]["severity"], "error");
+    assert_eq!(json[1]["line"], 2);
+    assert_eq!(json[1]["fix"]["edits"][0]["replacement"], ",");
+
+    let sarif: serde_json::Value = serde_json::from_str(&write(OutputFormat::Sarif)).unwrap();
+    let results = &sarif["runs"][0]["results"];
+    assert_eq!(results[1]["ruleId"], "table_ctor_comma");
+    assert_eq!(results[1]["level"], "error");
+    assert_eq!(results[1]["locations"][0]["physicalLocation"]["region"]["startLine"], 2);
+
+    let gitlab: serde_json::Value = serde_json::from_str(&write(OutputFormat::Gitlab)).unwrap();
+    assert_eq!(gitlab[0]["severity"], "minor");
+    assert_eq!(gitlab[1]["location"]["path"], "a.lua");
+    assert_ne!(gitlab[0]["fingerprint"], gitlab[1]["fingerprint"]);
+
+    // the same offending line twice in a file
+    let twice = "local t = {\n    a = 1\n}\nlocal t = {\n    a = 1\n}\n";
+    drive(twice, &mut linter);
+    let reports = collect_reports("a.lua", twice, &mut linter, None, None);
+    assert_eq!(reports[0].fingerprint, reports[1].fingerprint);
+    let mut out = Vec::new();
+    write_reports(OutputFormat::Gitlab, &reports, &mut out).unwrap();
+    let gitlab: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_ne!(gitlab[0]["fingerprint"], gitlab[1]["fingerprint"]);
+
+    let checkstyle = write(OutputFormat::Checkstyle);
+    assert!(checkstyle.contains(r#"<file name="a.lua">"#));
+    assert!(checkstyle.contains(
+        r#"<error line="2" column="14" severity="error" message="Table constructor should have a comma after the last field." source="lualint.table_ctor_comma"/>"#
+    ));
+}
+
+#[test]
+fn test_baseline_matches_moved_reports() {
+    use super::{collect_reports, drive};
+    use crate::cli::baseline::Baseline;
+
+    let reports_of = |lua_src: &str| {
+        let mut linter = build_config_linter(r#"{"table_ctor_comma": {}}"#).unwrap();
+        drive(lua_src, &mut linter);
+        collect_reports("./a.lua", lua_src, &mut linter, None, None)
+    };
+    let old_src = "local a = {\n    1\n}\nlocal b = {\n    2\n}\nlocal c = {\n    2\n}\n";
+    let baseline = Baseline::from_reports(&reports_of(old_src));
+    assert_eq!(baseline.entries.len(), 3);
+    assert_eq!(baseline.entries[0].file, "a.lua");
+    let baseline = Baseline::from_json(&baseline.to_json().to_string()).unwrap();
+
+    // lines moved down, `a` fixed, one `2` fixed and a new report added
+    let new_src = "local x = 1\n\nlocal a = {\n    1,\n}\nlocal b = {\n    2\n}\n\
+                   local c = {\n    2,\n}\nlocal d = {\n    3\n}\n";
+    let mut reports = reports_of(new_src);
+    assert_eq!(reports.len(), 2);
+    let stale = baseline.filter("a.lua", &mut reports);
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].report.pos.line, 13);
+    // `b` and `c` share a fingerprint, the last entry is the stale one
+    assert_eq!(stale, vec![baseline.entries[0].clone(), baseline.entries[2].clone()]);
+
+    assert!(Baseline::from_json(r#"{"entries": [{"file": "a.lua"}]}"#).is_err());
+}
+
+#[test]
+fn test_focus_ranges_from_diff() {
+    use crate::cli::SpecificRanges;
+
+    let diff = r#"diff --git a/src/a.lua b/src/a.lua
+index 83db48f..bf269f4 100644
+--- a/src/a.lua
++++ b/src/a.lua
+@@ -3 +3 @@ local a = 1
+-local b = 2
++local b = 3
+@@ -10,2 +10,0 @@ end
+-print(1)
+-print(2)
+@@ -20,0 +19,3 @@ end
++print(1)
++print(2)
++print(3)
+diff --git a/old.lua b/old.lua
+deleted file mode 100644
+--- a/old.lua
++++ /dev/null
+@@ -1,2 +0,0 @@
+-local x = 1
+-local y = 2
+diff --git a/new.lua b/new.lua
+new file mode 100644
+--- /dev/null
++++ b/new.lua
+@@ -0,0 +1,2 @@
++local x = 1
++local y = 2
+"#;
+    let ranges = SpecificRanges::from_unified_diff(diff);
+    let hits = |file: &str| -> Vec<usize> { (0..25).filter(|&l| ranges.hit(file, l)).collect() };
+    assert_eq!(hits("src/a.lua"), vec![3, 19, 20, 21]);
+    assert_eq!(hits("./src/a.lua"), vec![3, 19, 20, 21]);
+    assert_eq!(hits("new.lua"), vec![1, 2]);
+    assert!(hits("old.lua").is_empty());
+}
+
+#[test]
+fn test_rules_are_created_by_name() {
+    use crate::cli::{drive, print_lint_report};
+    use lualint::rules::{
+        self, LintReport, NodeWrapper, Pos, Registry, ReportLevel, Rule, RuleContext, RuleInfo,
+    };
+
+    // every built-in rule can be enabled from the config
+    for info in rules::BUILTIN_RULES {
+        let enabled_rules = format!(r#"{{"{}": {{}}}}"#, info.name);
+        assert!(build_config_linter(&enabled_rules).is_some(), "{}", info.name);
+    }
+
+    // and so can rules defined outside of lualint
+    struct NoTodo {
+        reports: Vec<LintReport>,
+    }
+    impl RuleContext for NoTodo {
+        fn get_reports(&self) -> &Vec<LintReport> {
+            &self.reports
+        }
+    }
+    impl Rule for NoTodo {
+        fn apply(rules: &mut Registry, _config: &serde_json::Value) -> Self {
+            rules.preprocess("no_todo", |rctx, node| {
+                let ctx: &mut NoTodo = rctx.downcast_mut().unwrap();
+                if let NodeWrapper::Source(source) = &node {
+                    for (i, line) in source.lines().enumerate() {
+                        if let Some(col) = line.find("TODO") {
+                            ctx.reports.push(LintReport {
+                                pos: Pos::new(i + 1, col + 1),
+                                level: ReportLevel::Info,
+                                msg: "Unresolved TODO".to_string(),
+                                fix: None,
+                            });
+                        }
+                    }
+                }
+                node
+            });
+            Self { reports: vec![] }
+        }
+
+        fn context(&self) -> &dyn RuleContext {
+            self
+        }
+    }
+    assert!(build_config_linter(r#"{"no_todo": {}}"#).is_none());
+    rules::register_rule(RuleInfo::of::<NoTodo>("no_todo", "Disallow TODO comments", "20230301", ""));
+    assert!(rules::ALL_RULES.lock().unwrap().contains_key("no_todo"));
+
+    let lua_src = "local a = 1\n-- TODO: remove\n";
+    let mut linter = build_config_linter(r#"{"no_todo": {}}"#).unwrap();
+    let mut stdout = Vec::new();
+    drive(lua_src, &mut linter);
+    let worst = print_lint_report("a.lua", Some(lua_src), &mut linter, None, None, &mut stdout);
+    assert_eq!(worst, Some(ReportLevel::Info));
+    assert!(String::from_utf8(stdout).unwrap().contains("[rule] no_todo:\n --> a.lua:2:3"));
+}
+
+#[test]
+fn test_rule_options_are_validated() {
+    use lualint::rules;
+
+    assert!(build_config_linter(r#"{"max_column_width": {"max_col": 100}}"#).is_some());
+    assert!(build_config_linter(r#"{"max_column_width": {"max_cols": 100}}"#).is_none());
+    assert!(build_config_linter(r#"{"max_column_width": {"max_col": "100"}}"#).is_none());
+    assert!(build_config_linter(r#"{"eof_blank_line": {"max_col": 100}}"#).is_none());
+
+    let info = rules::find_rule("max_column_width").unwrap();
+    assert_eq!(
+        info.validate(&serde_json::json!({"max_cols": 100})).unwrap_err(),
+        "rule `max_column_width`: unknown option `max_cols`, expected one of max_col, severity"
+    );
+    assert_eq!(
+        info.validate(&serde_json::json!({"max_col": -1})).unwrap_err(),
+        "rule `max_column_width`: option `max_col` must be a non-negative integer, got -1"
+    );
+    assert_eq!(info.option(&serde_json::json!({}), "max_col"), 80);
+
+    let schema = lualint::config::json_schema();
+    let rule_schema = &schema["properties"]["rules"]["properties"]["max_column_width"];
+    let options = &rule_schema["anyOf"][1]["properties"];
+    assert_eq!(options["max_col"]["default"], 80);
+    assert_eq!(options["severity"]["enum"][0], "off");
+    assert!(schema["properties"]["rules"]["properties"]["func_separation"].is_object());
+}
+
+#[test]
+#[should_panic(expected = "option `max_col` must be a non-negative integer, got \"80\"")]
+fn test_with_rule_rejects_invalid_options() {
+    use lualint::lint::LinterBuilder;
+    use lualint::rules::max_column_width::MaxColumnWidth;
+
+    let config = serde_json::json!({"max_col": "80"});
+    LinterBuilder::default().with_rule::<MaxColumnWidth>("max_column_width", &config);
+}
+
+#[test]
+fn test_language_server() {
+    use crate::cli::lsp::Server;
+    use lualint::config::Config;
+    use serde_json::{json, Value};
+
+    let root = std::env::temp_dir().join(format!("lualint_lsp_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("lualint.toml"), "root = true\n[rules]\ntable_ctor_comma = true\n")
+        .unwrap();
+    let uri = format!("file://{}/a%20b.lua", root.display());
+
+    let messages = |messages: &[Value]| -> Vec<u8> {
+        let mut input = vec![];
+        for message in messages {
+            let body = message.to_string();
+            input.extend(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).bytes());
+        }
+        input
+    };
+    let serve = |input: Vec<u8>| -> Vec<Value> {
+        let mut output = vec![];
+        Server::new(&mut output, Config::default()).serve(&mut &input[..]).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        output
+            .split("Content-Length: ")
+            .skip(1)
+            .map(|message| serde_json::from_str(message.split_once("\r\n\r\n").unwrap().1).unwrap())
+            .collect()
+    };
+
+    // the buffer is linted, not the file on disk, which does not exist
+    let text = "local t = {\n    a = 1\n}\n";
+    let open = json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": { "textDocument": { "uri": uri, "languageId": "lua", "text": text } },
+    });
+    let at_line = |line: u64| {
+        json!({ "textDocument": { "uri": uri }, "position": { "line": line, "character": 4 } })
+    };
+    let output = serve(messages(&[
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        open.clone(),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "textDocument/codeAction",
+            "params": {
+                "textDocument": { "uri": uri },
+                "range": {
+                    "start": { "line": 1, "character": 0 },
+                    "end": { "line": 1, "character": 0 },
+                },
+                "context": { "diagnostics": [] },
+            },
+        }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "textDocument/hover", "params": at_line(1) }),
+        json!({ "jsonrpc": "2.0", "id": 4, "method": "textDocument/hover", "params": at_line(0) }),
+        json!({ "jsonrpc": "2.0", "id": 5, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+        json!({ "jsonrpc": "2.0", "id": 6, "method": "shutdown" }),
+    ]));
+    assert_eq!(output.len(), 6);
+    assert_eq!(output[0]["result"]["capabilities"]["hoverProvider"], true);
+
+    let diagnostics = &output[1]["params"]["diagnostics"];
+    assert_eq!(output[1]["params"]["uri"], uri);
+    assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+    assert_eq!(diagnostics[0]["code"], "table_ctor_comma");
+    assert_eq!(diagnostics[0]["severity"], 2);
+    assert_eq!(diagnostics[0]["range"]["start"], json!({ "line": 1, "character": 9 }));
+
+    let edit = &output[2]["result"][0]["edit"]["changes"][&uri][0];
+    assert_eq!(edit["newText"], ",");
+    assert_eq!(edit["range"]["start"], json!({ "line": 1, "character": 9 }));
+    assert_eq!(output[2]["result"][0]["kind"], "quickfix");
+
+    let hover = output[3]["result"]["contents"]["value"].as_str().unwrap();
+    assert!(hover.starts_with("**table_ctor_comma**: "));
+    assert_eq!(output[4]["result"], Value::Null);
+    assert_eq!(output[5]["id"], 5);
+
+    // a changed config is picked up without restarting
+    std::fs::write(root.join("lualint.toml"), "root = true\n[rules]\neof_blank_line = true\n")
+        .unwrap();
+    let mut output = vec![];
+    let mut server = Server::new(&mut output, Config::default());
+    let open = json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": { "textDocument": { "uri": uri, "text": "local a = 1" } },
+    });
+    server.serve(&mut &messages(&[open])[..]).unwrap();
+    std::fs::write(root.join("lualint.toml"), "root = true\n[rules]\n").unwrap();
+    let config_uri = format!("file://{}/lualint.toml", root.display());
+    let changed = json!({
+        "jsonrpc": "2.0",
+        "method": "workspace/didChangeWatchedFiles",
+        "params": { "changes": [{ "uri": config_uri, "type": 2 }] },
+    });
+    server.serve(&mut &messages(&[changed])[..]).unwrap();
+    drop(server);
+    let output = String::from_utf8(output).unwrap();
+    let published: Vec<&str> = output.split("Content-Length: ").skip(1).collect();
+    assert_eq!(published.len(), 2);
+    assert!(published[0].contains("eof_blank_line"));
+    assert!(published[1].contains(r#""diagnostics":[]"#));
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_lint_unsaved_buffer() {
+    use super::{lint_buffer, LintOptions};
+    use lualint::config::ConfigLoader;
+
+    // a buffer of a file that does not exist yet, as read from stdin
+    let filename = "tests/not/saved/yet.lua";
+    let lua_src = "local t = {\n    a = 1\n}\n";
+    let mut linter = build_config_linter(r#"{"table_ctor_comma": {}}"#).unwrap();
+    let options =
+        LintOptions { fix: true, ignore: None, focus: None, baseline: None, write_text: true };
+    let mut output = Vec::new();
+    let (linted, fixed) = lint_buffer(filename, lua_src, &mut linter, &options, &mut output);
+    assert_eq!(fixed, "local t = {\n    a = 1,\n}\n");
+    assert_eq!(linted.reports.len(), 1);
+    assert_eq!(linted.reports[0].report.pos.file, filename);
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(" --> tests/not/saved/yet.lua:2:9"));
+    assert!(output.contains("[fix] table_ctor_comma: 1"));
+    assert!(!std::path::Path::new(filename).exists());
+
+    // its config is looked up from the closest existing directory
+    let config = ConfigLoader::default().load_for_file(std::path::Path::new(filename)).unwrap();
+    let from_tests = ConfigLoader::default().load_for_file(std::path::Path::new("tests/a.lua"));
+    assert_eq!(config.rules.len(), from_tests.unwrap().rules.len());
+}
+
+#[test]
+fn test_linter_reused_for_several_files() {
+    use lualint::driver::lint_with;
+
+    let mut linter = build_config_linter(
+        r#"{"max_column_width": {"max_col": 20}, "eof_blank_line": {}, "table_ctor_comma": {}}"#,
+    )
+    .unwrap();
+    let bad = "local t = { a = 1, b = 2, c = 3 }\nlocal u = {\n    a = 1\n}";
+    let result = lint_with(&mut linter, "a.lua", bad);
+    let rules: Vec<&str> = result.reports.iter().map(|r| r.rule.as_str()).collect();
+    assert_eq!(rules, vec!["eof_blank_line", "max_column_width", "table_ctor_comma"]);
+    assert!(result.reports.iter().all(|r| r.report.pos.file == "a.lua"));
+    let fixed = "local t = { a = 1, b = 2, c = 3 }\nlocal u = {\n    a = 1,\n}\n";
+    assert_eq!(result.fixed.as_deref(), Some(fixed));
+
+    // nothing is left over from a.lua
+    let result = lint_with(&mut linter, "b.lua", "local a = 1\n");
+    assert!(result.reports.is_empty());
+    assert_eq!(result.fixed, None);
+
+    let result = lint_with(&mut linter, "c.lua", "local = 1\n");
+    assert_eq!(result.parse_errors.len(), 1);
+    let result = lint_with(&mut linter, "d.lua", bad);
+    assert!(result.parse_errors.is_empty());
+    assert_eq!(result.reports.len(), 3);
+    assert!(result.reports.iter().all(|r| r.report.pos.file == "d.lua"));
+}
+
+#[test]
+fn test_rules_listen_to_scopes() {
+    use lualint::{
+        driver::lint_with,
+        lint::{scope::Access, LinterBuilder, ScopeAnalysis},
+        rules::{LintReport, Pos, Registry, ReportLevel, Rule, RuleContext},
+    };
+
+    // reports the globals assigned in a file
+    struct GlobalWrites {
+        reports: Vec<LintReport>,
+    }
+    impl RuleContext for GlobalWrites {
+        fn get_reports(&self) -> &Vec<LintReport> {
+            &self.reports
+        }
+    }
+    impl Rule for GlobalWrites {
+        fn apply(rules: &mut Registry, _config: &serde_json::Value) -> Self {
+            rules.listen_scopes("global_writes", |rctx, scopes: &ScopeAnalysis| {
+                let ctx: &mut GlobalWrites = rctx.downcast_mut().unwrap();
+                for global in scopes.globals().filter(|r| r.access == Access::Write) {
+                    ctx.reports.push(LintReport {
+                        pos: Pos::new(global.pos.line, global.pos.col),
+                        level: ReportLevel::Warning,
+                        msg: format!("Global `{}` is assigned", global.name),
+                        fix: None,
+                    });
+                }
+            });
+            Self { reports: vec![] }
+        }
+
+        fn context(&self) -> &dyn RuleContext {
+            self
+        }
+    }
+
+    let config = serde_json::json!({});
+    let mut linter =
+        LinterBuilder::default().with_rule::<GlobalWrites>("global_writes", &config).build();
+    let result = lint_with(&mut linter, "a.lua", "local a = 1\na = 2\nb = a\n");
+    let msgs: Vec<&str> = result.reports.iter().map(|r| r.report.msg.as_str()).collect();
+    assert_eq!(msgs, vec!["Global `b` is assigned"]);
+    assert_eq!(result.reports[0].report.pos.line, 3);
+}
+
+/// Lints `src` with the rule `T` alone. Returns its reports as `line:col message`
+/// and the source with their fixes applied.
+fn lint_rule<T: lualint::rules::Rule + lualint::rules::RuleContext>(
+    rule_name: &str,
+    config: serde_json::Value,
+    src: &str,
+) -> (Vec<String>, String) {
+    use lualint::driver::lint_with;
+    use lualint::lint::LinterBuilder;
+
+    let mut linter = LinterBuilder::default().with_rule::<T>(rule_name, &config).build();
+    let result = lint_with(&mut linter, "a.lua", src);
+    let reports = result
+        .reports
+        .iter()
+        .map(|r| format!("{}:{} {}", r.report.pos.line, r.report.pos.col, r.report.msg))
+        .collect();
+    (reports, result.fixed.unwrap_or_else(|| src.to_string()))
+}
+
+#[test]
+fn test_unused_variable() {
+    use lualint::rules::unused_variable::UnusedVariable;
+
+    let lua_src = r#"local a, _b = 1, 2
+local c
+c = 3
+local function f(x, y, ...)
+    for i = 1, 10 do end
+    for k, v in pairs(y) do print(v) end
+end
+local t = {}
+function t:m(_x) end
+return f
+"#;
+    let lint = |config| lint_rule::<UnusedVariable>("unused_variable", config, lua_src).0;
+    assert_eq!(
+        lint(serde_json::json!({})),
+        vec![
+            "1:7 Unused local variable `a`",
+            "2:7 Local variable `c` is assigned but never read",
+            "4:18 Unused parameter `x`",
+            "4:24 Unused variable length argument",
+            "5:9 Unused loop variable `i`",
+            "6:9 Unused loop variable `k`",
+        ]
+    );
+    let reports = lint(serde_json::json!({"ignore_self": false, "ignore_varargs": true}));
+    assert_eq!(reports.len(), 6);
+    assert!(!reports.iter().any(|r| r.contains("variable length")));
+    assert_eq!(reports.last().unwrap(), "9:11 Unused parameter `self`");
+}
+
+#[test]
+fn test_undefined_global() {
+    use lualint::lint::LinterBuilder;
+    use lualint::rules::undefined_global::UndefinedGlobal;
+
+    let lua_src = r#"local ngx = ngx
+ngx.log(ngx.ERRR, "x")
+pirnt(string.fromat("%d", 1))
+counter = 1
+string = nil
+_M.version = "1.0"
+function describe_all() return kong.log, ... end
+"#;
+    let lint = |config| lint_rule::<UndefinedGlobal>("undefined_global", config, lua_src).0;
+    assert_eq!(
+        lint(serde_json::json!({})),
+        vec![
+            "2:13 Accessing undefined field `ERRR` of global `ngx`, did you mean `ERR`?",
+            "3:1 Accessing undefined global `pirnt`, did you mean `print`?",
+            "3:14 Accessing undefined field `fromat` of global `string`, did you mean `format`?",
+            "4:1 Setting undefined global `counter`",
+            "5:1 Setting read-only global `string`",
+            "6:1 Accessing undefined global `_M`, did you mean `_G`?",
+            "7:10 Setting undefined global `describe_all`",
+            "7:32 Accessing undefined global `kong`",
+        ]
+    );
+    let config = serde_json::json!({
+        "std": "kong+busted",
+        "globals": ["counter", "describe_all", "_M"],
+        "read_globals": ["string.fromat"],
+    });
+    assert_eq!(
+        lint(config),
+        vec![
+            "2:13 Accessing undefined field `ERRR` of global `ngx`, did you mean `ERR`?",
+            "3:1 Accessing undefined global `pirnt`, did you mean `print`?",
+            "5:1 Setting read-only global `string`",
+        ]
+    );
+    let config = serde_json::json!({"std": "lua53"});
+    let error = LinterBuilder::default().with_rule_by_name("undefined_global", &config).err();
+    assert_eq!(
+        error.as_deref(),
+        Some(
+            "rule `undefined_global`: option `std`: unknown std preset `lua53`, expected one of \
+             lua51, luajit, ngx_lua, kong, busted"
+        )
+    );
+}
+
+#[test]
+fn test_shadowing() {
+    use lualint::lint::LinterBuilder;
+    use lualint::rules::shadowing::Shadowing;
+
+    let lua_src = r#"local type = type
+local err
+local function f(table, _x)
+    local err
+    local err
+    if table then
+        local ok, table = pcall(f)
+    end
+    for _, _ in pairs(table) do end
+    for i = 1, 3 do local i = i * 2 end
+    for k in pairs(table) do local k = k end
+end
+"#;
+    let lint = |config| lint_rule::<Shadowing>("shadowing", config, lua_src).0;
+    assert_eq!(
+        lint(serde_json::json!({})),
+        vec![
+            "3:18 Parameter `table` shadows the standard global `table`",
+            "4:11 Local `err` shadows the upvalue `err` declared at 2:7",
+            "5:11 Local `err` redeclares `err` declared at 4:11 in the same scope",
+            "7:19 Local `table` shadows the parameter `table` declared at 3:18",
+            "10:27 Local `i` shadows the loop variable `i` declared at 10:9",
+            "11:36 Local `k` shadows the loop variable `k` declared at 11:9",
+        ]
+    );
+    assert_eq!(
+        lint(serde_json::json!({"allow_same_scope": true, "std": "busted"})),
+        vec![
+            "4:11 Local `err` shadows the upvalue `err` declared at 2:7",
+            "7:19 Local `table` shadows the parameter `table` declared at 3:18",
+            "10:27 Local `i` shadows the loop variable `i` declared at 10:9",
+            "11:36 Local `k` shadows the loop variable `k` declared at 11:9",
+        ]
+    );
+    let config = serde_json::json!({"std": "ngx_lua+luajit+lua53"});
+    let error = LinterBuilder::default().with_rule_by_name("shadowing", &config).err();
+    assert!(error.is_some_and(|e| e.starts_with("rule `shadowing`: option `std`: unknown std")));
+}
+
+#[test]
+fn test_indent_with_spaces() {
+    use lualint::rules::indent_with_spaces::IndentWithSpaces;
+
+    let lint = |config, src| lint_rule::<IndentWithSpaces>("indent_with_spaces", config, src);
+
+    let lua_src = "local function f(a)
+  if a then
+\t\treturn {
+          x = 1,
+        }
+    else -- comment
+     -- about b
+       foo(
+      a,
+          function()
+            return b
+          end
+     )
+    end
+   local s = [[
+  kept]] .. a
+      .. 'x'
+    return s
+end
+";
+    let (reports, fixed) = lint(serde_json::json!({}), lua_src);
+    assert_eq!(
+        reports,
+        vec![
+            "2:1 Line should be indented with 4 spaces, found 2",
+            "3:1 Line should be indented with 8 spaces, found tabs",
+            "4:1 Line should be indented with 12 spaces, found 10",
+            "8:1 Line should be indented with 8 spaces, found 7",
+            "9:1 Line should be indented with 12 spaces, found 6",
+            "10:1 Line should be indented with 12 spaces, found 10",
+            "11:1 Line should be indented with 16 spaces, found 12",
+            "12:1 Line should be indented with 12 spaces, found 10",
+            "13:1 Line should be indented with 8 spaces, found 5",
+            "15:1 Line should be indented with 4 spaces, found 3",
+        ]
+    );
+    let expected = "local function f(a)
+    if a then
+        return {
+            x = 1,
+        }
+    else -- comment
+     -- about b
+        foo(
+            a,
+            function()
+                return b
+            end
+        )
+    end
+    local s = [[
+  kept]] .. a
+      .. 'x'
+    return s
+end
+";
+    assert_eq!(fixed, expected);
+
+    // continued expressions are only checked for tabs
+    let src = "if a then\n    b = 1\nend\nc = d\n\t.. e\n";
+    let (_, fixed) = lint(serde_json::json!({"width": 2}), src);
+    assert_eq!(fixed, "if a then\n  b = 1\nend\nc = d\n  .. e\n");
+}
+
+#[test]
+fn test_operator_spacing() {
+    use lualint::rules::operator_spacing::OperatorSpacing;
+
+    let lint = |config, src| lint_rule::<OperatorSpacing>("operator_spacing", config, src);
+    let lua_src = "local a=1
+local b <const>  =  a+ 2*3
+b = - a .. #  t
+c,d =a^2, - -a
+local s = a ..
+    b -- kept
+local ok = not a and a<=b
+";
+    let (reports, fixed) = lint(serde_json::json!({}), lua_src);
+    assert_eq!(
+        reports,
+        vec![
+            "1:8 Assignment `=` should have one space on each side",
+            "2:18 Assignment `=` should have one space on each side",
+            "2:22 Operator `+` should have one space on each side",
+            "2:25 Operator `*` should have one space on each side",
+            "3:5 Unary operator `-` should not be followed by a space",
+            "3:12 Unary operator `#` should not be followed by a space",
+            "4:5 Assignment `=` should have one space on each side",
+            "4:7 Operator `^` should have one space on each side",
+            "7:23 Operator `<=` should have one space on each side",
+        ]
+    );
+    let expected = "local a = 1
+local b <const> = a + 2 * 3
+b = -a .. #t
+c,d = a ^ 2, - -a
+local s = a ..
+    b -- kept
+local ok = not a and a <= b
+";
+    assert_eq!(fixed, expected);
+
+    // left-hand sides ending with a call, an index or parentheses
+    let spaced = "s = f(a) .. b\ns = x[1] .. b\nt[1] = 2\nok = f(x) == 1\n\
+                  s = (\"%d\"):format(a) .. b\ns = (a) .. b\n";
+    let (reports, fixed) = lint(serde_json::json!({}), spaced);
+    assert!(reports.is_empty(), "{reports:?}");
+    assert_eq!(fixed, spaced);
+    let cramped = "s = f(a)..b\ns = x[1]..b\nt[1]=2\nok = f(x)==1\n\
+                   s = (\"%d\"):format(a)..b\ns = (a)..b\n";
+    assert_eq!(lint(serde_json::json!({}), cramped).1, spaced);
+
+    let config = serde_json::json!({
+        "space_after_unary": true, "check_concat": false, "exempt_caret": true
+    });
+    assert_eq!(lint(config, "x = -a..b^2 + #t\n").1, "x = - a..b^2 + # t\n");
+}
+
+#[test]
+fn test_no_trailing_semicolon() {
+    use lualint::rules::no_trailing_semicolon::NoTrailingSemicolon;
+
+    let lint = |config, src| lint_rule::<NoTrailingSemicolon>("no_trailing_semicolon", config, src);
+    let lua_src = std::fs::read_to_string("tests/no_trailing_semicolon.lua").unwrap();
+    let (reports, fixed) = lint(serde_json::json!({}), &lua_src);
+    assert_eq!(
+        reports,
+        vec![
+            "1:4 Statement ends with a trailing semicolon",
+            "14:6 Semicolon separates statements on one line",
+            "3:8 Statement ends with a trailing semicolon",
+            "4:8 Statement ends with a trailing semicolon",
+            "5:8 Statement ends with a trailing semicolon",
+            "18:8 Statement ends with a trailing semicolon",
+            "19:11 Statement ends with a trailing semicolon",
+        ]
+    );
+    let expected =
+        lua_src.replace("();", "()").replace("a = 1; b", "a = 1 b").replace("return;", "return");
+    assert_eq!(fixed, expected);
+
+    let (_, fixed) = lint(serde_json::json!({"allow_separator": true}), "a = 1; b = 2;\nc = 3;\n");
+    assert_eq!(fixed, "a = 1; b = 2\nc = 3\n");
+}
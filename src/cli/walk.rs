@@ -0,0 +1,67 @@
+use std::{collections::HashSet, path::Path};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use log::error;
+
+/// Expands the paths given on the command line into the list of files to lint.
+/// Files are taken as is, directories are walked recursively for files matching
+/// `include` (`*.lua` when empty), skipping hidden and `.gitignore`d entries.
+/// Anything matching `exclude` is dropped. Globs are matched against the path
+/// as it would be printed, so `spec/**` only matches below a `spec` argument
+/// while `**/spec/**` matches everywhere.
+pub fn collect_files(
+    paths: &[String],
+    include: &[String],
+    exclude: &[String],
+) -> Result<Vec<String>, String> {
+    let default_include = vec!["*.lua".to_string()];
+    let include = build_glob_set(if include.is_empty() { &default_include } else { include })?;
+    let exclude = build_glob_set(exclude)?;
+
+    let mut files: Vec<String> = vec![];
+    for path in paths {
+        if !Path::new(path).is_dir() {
+            // explicit files are linted even if ignored, `lint_file` reports missing ones
+            if !exclude.is_match(path) {
+                files.push(path.to_string());
+            }
+            continue;
+        }
+        let mut dir_files = vec![];
+        for entry in ignore::WalkBuilder::new(path).require_git(false).build() {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    error!("Error walking {}: {}", path, e);
+                    continue;
+                }
+            };
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            let file = entry.path().to_string_lossy().to_string();
+            if include.is_match(&file) && !exclude.is_match(&file) {
+                dir_files.push(file);
+            }
+        }
+        dir_files.sort();
+        files.extend(dir_files);
+    }
+    let mut seen = HashSet::new();
+    files.retain(|file| seen.insert(file.clone()));
+    Ok(files)
+}
+
+fn build_glob_set(globs: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob).map_err(|e| format!("Invalid glob `{glob}`: {e}"))?);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// Spells a path the same way whether it came from the command line, a walk or
+/// a diff, so that it can be compared as a string.
+pub fn normalize_path(path: &str) -> String {
+    path.strip_prefix("./").unwrap_or(path).replace('\\', "/")
+}
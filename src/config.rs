@@ -0,0 +1,248 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use globset::Glob;
+use linked_hash_map::LinkedHashMap;
+use serde_json::{json, Value};
+
+use crate::{lint::LuaVersion, rules};
+
+/// File names looked up in every directory from a linted file up to the root.
+pub const CONFIG_FILE_NAMES: [&str; 2] = ["lualint.toml", ".lualint.json"];
+
+/// Lint settings of a project.
+///
+/// ```toml
+/// root = true              # don't look for config files in parent directories
+/// lua_version = "luajit"
+/// exclude = ["spec/**"]    # relative to the directory of the config file
+///
+/// [rules]
+/// eof_blank_line = true    # enable with default options
+/// func_separation = false  # disable a rule enabled by a parent config
+///
+/// [rules.max_column_width]
+/// max_col = 100
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub root: bool,
+    pub lua_version: Option<LuaVersion>,
+    /// `(base_dir, glob)` pairs, the glob is matched against paths relative to `base_dir`
+    pub exclude: Vec<(PathBuf, String)>,
+    /// enabled rules and their options, `false` marks a rule disabled by this config
+    pub rules: LinkedHashMap<String, Value>,
+}
+
+impl Config {
+    pub fn from_toml(src: &str, base_dir: &Path) -> Result<Self, String> {
+        let value: toml::Value = toml::from_str(src).map_err(|e| e.to_string())?;
+        let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+        Self::from_value(value, base_dir)
+    }
+
+    pub fn from_json(src: &str, base_dir: &Path) -> Result<Self, String> {
+        let value = serde_json::from_str(src).map_err(|e| e.to_string())?;
+        Self::from_value(value, base_dir)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let src = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let config = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&src, base_dir),
+            _ => Self::from_json(&src, base_dir),
+        };
+        config.map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Builds a config from the flat `{ "rule_name": { ...options } }` map given to `--rules`.
+    /// A `lua_version` key is accepted there as well.
+    pub fn from_rules(rules: Vec<(String, Value)>) -> Result<Self, String> {
+        let mut config = Config::default();
+        for (rule_name, rule_config) in rules {
+            if rule_name == "lua_version" {
+                config.lua_version = Some(parse_lua_version(&rule_config)?);
+            } else {
+                config.add_rule(rule_name, rule_config)?;
+            }
+        }
+        Ok(config)
+    }
+
+    fn from_value(value: Value, base_dir: &Path) -> Result<Self, String> {
+        let table = match value {
+            Value::Object(table) => table,
+            other => return Err(format!("expected a table, got {other}")),
+        };
+        let mut config = Config::default();
+        for (key, value) in table {
+            match key.as_str() {
+                "root" => {
+                    config.root = value.as_bool().ok_or("`root` must be a boolean")?;
+                }
+                "lua_version" => config.lua_version = Some(parse_lua_version(&value)?),
+                "exclude" => {
+                    let globs = value.as_array().ok_or("`exclude` must be a list of globs")?;
+                    for glob in globs {
+                        let glob = glob.as_str().ok_or("`exclude` must be a list of globs")?;
+                        Glob::new(glob).map_err(|e| format!("invalid glob `{glob}`: {e}"))?;
+                        config.exclude.push((base_dir.to_path_buf(), glob.to_string()));
+                    }
+                }
+                "rules" => {
+                    let rules = match value {
+                        Value::Object(rules) => rules,
+                        _ => return Err("`rules` must be a table".to_string()),
+                    };
+                    for (rule_name, rule_config) in rules {
+                        config.add_rule(rule_name, rule_config)?;
+                    }
+                }
+                other => return Err(format!("unknown key `{other}`")),
+            }
+        }
+        Ok(config)
+    }
+
+    fn add_rule(&mut self, rule_name: String, rule_config: Value) -> Result<(), String> {
+        let rule_config = match rule_config {
+            Value::Bool(true) | Value::Null => Value::Object(Default::default()),
+            Value::Bool(false) | Value::Object(_) => rule_config,
+            other => {
+                return Err(format!(
+                    "rule `{rule_name}` must be a boolean or a table of options, got {other}"
+                ))
+            }
+        };
+        self.rules.insert(rule_name, rule_config);
+        Ok(())
+    }
+
+    /// Applies `other` on top of `self`: its options win, its `false` rules are disabled.
+    pub fn merge(&mut self, other: &Config) {
+        if other.lua_version.is_some() {
+            self.lua_version = other.lua_version;
+        }
+        self.exclude.extend(other.exclude.iter().cloned());
+        for (rule_name, rule_config) in &other.rules {
+            match (self.rules.get_mut(rule_name), rule_config) {
+                (Some(Value::Object(options)), Value::Object(overrides)) => {
+                    options.extend(overrides.iter().map(|(k, v)| (k.clone(), v.clone())));
+                }
+                _ => {
+                    self.rules.insert(rule_name.clone(), rule_config.clone());
+                }
+            }
+        }
+    }
+
+    /// The enabled rules and their options.
+    pub fn enabled_rules(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.rules.iter().filter(|(_, rule_config)| rule_config.is_object())
+    }
+
+    pub fn is_excluded(&self, file: &Path) -> bool {
+        let file = std::fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        self.exclude.iter().any(|(base_dir, glob)| {
+            let base_dir = std::fs::canonicalize(base_dir).unwrap_or_else(|_| base_dir.clone());
+            match (file.strip_prefix(base_dir), Glob::new(glob)) {
+                (Ok(relative), Ok(glob)) => glob.compile_matcher().is_match(relative),
+                _ => false,
+            }
+        })
+    }
+}
+
+/// JSON Schema of a config file, for completion and checks in editors. The
+/// rules are those registered when it is called.
+pub fn json_schema() -> Value {
+    let all_rules = rules::ALL_RULES.lock().unwrap();
+    let rules: serde_json::Map<String, Value> =
+        all_rules.iter().map(|(name, info)| (name.to_string(), info.json_schema())).collect();
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "lualint config",
+        "type": "object",
+        "properties": {
+            "root": {
+                "type": "boolean",
+                "description": "Don't look for config files in parent directories",
+            },
+            "lua_version": {
+                "enum": ["5.1", "5.2", "5.3", "5.4", "luajit"],
+                "description": "Lua version the files are written for",
+            },
+            "exclude": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Globs of files not to lint, relative to the config file",
+            },
+            "rules": {
+                "type": "object",
+                "properties": rules,
+                "additionalProperties": false,
+            },
+        },
+        "additionalProperties": false,
+    })
+}
+
+fn parse_lua_version(value: &Value) -> Result<LuaVersion, String> {
+    match value.as_str() {
+        Some(lua_version) => lua_version.parse().map_err(|e| format!("invalid `lua_version`: {e}")),
+        None => Err(format!("invalid `lua_version`: expected a string, got {value}")),
+    }
+}
+
+/// Finds and merges the config files that apply to a file, caching them per directory.
+#[derive(Default)]
+pub struct ConfigLoader {
+    cache: HashMap<PathBuf, Config>,
+}
+
+impl ConfigLoader {
+    /// Merges the config files from the outermost directory (or the closest
+    /// `root = true` one) down to the directory of `file`. The file need not
+    /// exist, e.g. for an unsaved buffer, the search starts from the closest
+    /// directory that does.
+    pub fn load_for_file(&mut self, file: &Path) -> Result<Config, String> {
+        let mut dir = match file.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        while !dir.is_dir() && dir != Path::new(".") {
+            dir = match dir.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+        }
+        let dir = std::fs::canonicalize(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        self.load_for_dir(&dir)
+    }
+
+    fn load_for_dir(&mut self, dir: &Path) -> Result<Config, String> {
+        if let Some(config) = self.cache.get(dir) {
+            return Ok(config.clone());
+        }
+        let own = match CONFIG_FILE_NAMES.iter().map(|name| dir.join(name)).find(|p| p.is_file()) {
+            Some(path) => Some(Config::from_file(&path)?),
+            None => None,
+        };
+        let config = match (own, dir.parent()) {
+            (Some(own), _) if own.root => own,
+            (own, Some(parent)) => {
+                let mut config = self.load_for_dir(parent)?;
+                if let Some(own) = own {
+                    config.merge(&own);
+                }
+                config
+            }
+            (own, None) => own.unwrap_or_default(),
+        };
+        self.cache.insert(dir.to_path_buf(), config.clone());
+        Ok(config)
+    }
+}
@@ -0,0 +1,265 @@
+use std::fmt;
+
+use linked_hash_map::LinkedHashMap;
+
+use crate::{
+    config::Config,
+    lint::{self, suppression, Linter, LinterBuilder, ScopeAnalysis, Suppressions},
+    rules::{
+        self,
+        syntax_error::{self, SyntaxError},
+        LintReport, ReportLevel,
+    },
+};
+
+/// A report of the rule it was raised by.
+#[derive(Clone, Debug)]
+pub struct RuleReport {
+    pub rule: String,
+    pub report: LintReport,
+    /// identifies the report by its rule, message and the content of its line
+    /// rather than the line number, see `fingerprint`
+    pub fingerprint: String,
+}
+
+impl RuleReport {
+    /// `line` is the source line the report is on, empty for whole-file reports.
+    pub fn new(rule: &str, report: LintReport, line: &str) -> Self {
+        let fingerprint = fingerprint(&[rule, line.trim(), &report.msg]);
+        Self { rule: rule.to_string(), report, fingerprint }
+    }
+}
+
+/// 64-bit FNV-1a of `parts`, stable across runs and Rust versions unlike `DefaultHasher`.
+pub fn fingerprint(parts: &[&str]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in parts {
+        // separate the parts so that `ab`+`c` and `a`+`bc` differ
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{hash:016x}")
+}
+
+/// What `lint_source` found.
+#[derive(Clone, Debug, Default)]
+pub struct LintResult {
+    /// reports of the enabled rules that are not suppressed, in rule order
+    pub reports: Vec<RuleReport>,
+    /// the source with the safe fixes of `reports` applied, `None` if nothing changed
+    pub fixed: Option<String>,
+    /// syntax errors and syntax the configured Lua version does not support;
+    /// when the source does not parse, the rules only see its tokens or nothing
+    pub parse_errors: Vec<LintReport>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LintError {
+    /// the config enables an unknown rule or gives it invalid options
+    Config(String),
+}
+
+impl fmt::Display for LintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LintError::Config(msg) => write!(f, "invalid config: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for LintError {}
+
+/// Lints `src` with the rules enabled in `config`. Nothing is printed or
+/// written, the reports have no file name.
+///
+/// ```
+/// let config = lualint::config::Config::from_toml(
+///     "[rules]\ntable_ctor_comma = true\n",
+///     std::path::Path::new("."),
+/// )
+/// .unwrap();
+/// let result = lualint::lint_source("local t = {\n    a = 1\n}\n", &config).unwrap();
+/// assert_eq!(result.reports[0].rule, "table_ctor_comma");
+/// assert_eq!(result.fixed.as_deref(), Some("local t = {\n    a = 1,\n}\n"));
+/// ```
+pub fn lint_source(src: &str, config: &Config) -> Result<LintResult, LintError> {
+    let mut linter = build_linter(config)?;
+    Ok(lint_with(&mut linter, "", src))
+}
+
+/// Lints `src` with a linter that may have linted other files before, the
+/// reports are those of `src` only and carry `filename`.
+pub fn lint_with(linter: &mut Linter, filename: &str, src: &str) -> LintResult {
+    drive(src, linter);
+    let (parse_errors, reports): (Vec<RuleReport>, Vec<RuleReport>) =
+        collect_reports(filename, src, linter)
+            .into_iter()
+            .partition(|rule_report| rule_report.rule == syntax_error::RULE_NAME);
+    let (fixed, _) = apply_fixes(src, &reports);
+    LintResult {
+        reports,
+        fixed: Some(fixed).filter(|fixed| fixed != src),
+        parse_errors: parse_errors.into_iter().map(|rule_report| rule_report.report).collect(),
+    }
+}
+
+/// A linter with the rules enabled in `config`.
+pub fn build_linter(config: &Config) -> Result<Linter, LintError> {
+    let mut linter_builder = LinterBuilder::default();
+    if let Some(lua_version) = config.lua_version {
+        linter_builder = linter_builder.with_lua_version(lua_version);
+    }
+    for (rule_name, rule_config) in config.enabled_rules() {
+        let severity = rule_config.get("severity");
+        match severity.map(|level| level.as_str().map(str::parse::<ReportLevel>)) {
+            None => {}
+            Some(Some(Ok(level))) => {
+                linter_builder = linter_builder.with_severity(rule_name, level);
+            }
+            _ if severity.is_some_and(|level| level == "off") => continue,
+            _ => {
+                return Err(LintError::Config(format!(
+                    "Invalid `severity` of rule `{}`: expected one of off, info, warning or \
+                     error, got {}",
+                    rule_name,
+                    severity.unwrap()
+                )));
+            }
+        }
+        linter_builder =
+            linter_builder.with_rule_by_name(rule_name, rule_config).map_err(LintError::Config)?;
+    }
+    Ok(linter_builder.build())
+}
+
+/// Lints `lua_src`, the reports are kept in `linter` for `collect_reports`
+/// and `apply_fixes` fixes the source from them. If the source does not parse,
+/// the error is reported under the `syntax_error` pseudo-rule. The reports of
+/// the previous `drive` are dropped.
+pub fn drive(lua_src: &str, linter: &mut Linter) {
+    linter.begin_file();
+    let lua_src = linter.rule_registry.trigger_preprocess(lua_src);
+    let tokens = match full_moon::tokenizer::tokens(lua_src.as_str()) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            let ctx = SyntaxError::from_tokenizer_error(&e);
+            linter.rule_registry.bind_ctx(syntax_error::RULE_NAME, Box::new(ctx));
+            return;
+        }
+    };
+    linter.suppressions = Suppressions::from_tokens(&tokens);
+    let tokens = lint::lint_tokens(&tokens, linter);
+    let input_ast = match full_moon::ast::Ast::from_tokens(tokens) {
+        Ok(ast) => ast,
+        Err(e) => {
+            let ctx = SyntaxError::from_ast_error(&e);
+            linter.rule_registry.bind_ctx(syntax_error::RULE_NAME, Box::new(ctx));
+            return;
+        }
+    };
+
+    let unsupported = lint::lua_version::check_syntax(&input_ast, linter.lua_version);
+    if !unsupported.is_empty() {
+        let ctx = SyntaxError { reports: unsupported };
+        linter.rule_registry.bind_ctx(syntax_error::RULE_NAME, Box::new(ctx));
+    }
+
+    if linter.rule_registry.has_scope_listeners() {
+        let scopes = ScopeAnalysis::from_ast(&input_ast);
+        linter.rule_registry.notify_scopes(&scopes);
+    }
+
+    lint::lint_visitor::lint_ast(&input_ast, linter);
+}
+
+/// Lints nothing: picks the reports of the last `drive` of `lua_src` that
+/// are not suppressed, with their configured level and `pos.file` set, in rule order.
+pub fn collect_reports(filename: &str, lua_src: &str, linter: &mut Linter) -> Vec<RuleReport> {
+    let mut rule_reports: Vec<RuleReport> = vec![];
+    let lines: Vec<&str> = lua_src.lines().collect();
+    let severities = &linter.severities;
+    let mut collect = |name: &str, reports: &[LintReport], suppressions: &mut Suppressions| {
+        reports.iter().for_each(|report: &LintReport| {
+            if suppressions.suppresses(name, report.pos.line) {
+                return;
+            }
+            let mut report_tmp: LintReport = report.clone();
+            report_tmp.pos.file = filename.to_string();
+            report_tmp.level = severities.get(name).copied().unwrap_or(report.level);
+            let line = report.pos.line.checked_sub(1).and_then(|i| lines.get(i));
+            rule_reports.push(RuleReport::new(name, report_tmp, line.unwrap_or(&"")));
+        });
+    };
+    linter.rule_registry.rule_ctx.iter().for_each(|(name, rule)| {
+        collect(name, rule.get_reports(), &mut linter.suppressions);
+    });
+    if linter.report_unused_suppressions {
+        let unused = linter.suppressions.unused_reports();
+        collect(suppression::UNUSED_SUPPRESSION, &unused, &mut Suppressions::default());
+    }
+    rule_reports
+}
+
+/// `src` with the safe fixes of `reports` applied, and how many fixes of each
+/// rule were applied. Only the reports passed in are fixed, so suppressed
+/// reports leave their lines alone. A fix overlapping one that starts
+/// earlier is skipped, it is offered again by the next run.
+pub fn apply_fixes(src: &str, reports: &[RuleReport]) -> (String, LinkedHashMap<String, usize>) {
+    let mut fixes: Vec<(&str, &[rules::TextEdit])> = reports
+        .iter()
+        .filter_map(|rule_report| match &rule_report.report.fix {
+            Some(fix) if fix.safe && !fix.edits.is_empty() => {
+                Some((rule_report.rule.as_str(), fix.edits.as_slice()))
+            }
+            _ => None,
+        })
+        .collect();
+    let range = |edits: &[rules::TextEdit]| {
+        let start = edits.iter().map(|edit| edit.start).min().unwrap_or(0);
+        let end = edits.iter().map(|edit| edit.end).max().unwrap_or(0);
+        (start, end)
+    };
+    fixes.sort_by_key(|(_, edits)| range(edits));
+
+    let mut edits = vec![];
+    let mut counts: LinkedHashMap<String, usize> = LinkedHashMap::new();
+    let mut cursor = 0;
+    for (rule, fix_edits) in fixes {
+        let (start, end) = range(fix_edits);
+        if start < cursor || end > src.len() {
+            continue;
+        }
+        cursor = end;
+        edits.extend(fix_edits.iter().cloned());
+        *counts.entry(rule.to_string()).or_insert(0) += 1;
+    }
+    (rules::apply_edits(src, &edits), counts)
+}
+
+#[test]
+fn test_lint_source() {
+    let config = Config::from_json(
+        r#"{"lua_version": "5.1", "rules": {"eof_blank_line": {"severity": "error"}}}"#,
+        std::path::Path::new("."),
+    )
+    .unwrap();
+    let result = lint_source("local a = 1\n", &config).unwrap();
+    assert!(result.reports.is_empty() && result.parse_errors.is_empty());
+    assert_eq!(result.fixed, None);
+
+    let result = lint_source("local a = 1", &config).unwrap();
+    assert_eq!(result.reports[0].report.level, ReportLevel::Error);
+    assert_eq!(result.fixed.as_deref(), Some("local a = 1\n"));
+
+    let result = lint_source("goto done\n::done::\n", &config).unwrap();
+    assert!(result.reports.is_empty());
+    assert_eq!(result.parse_errors.len(), 2);
+    let result = lint_source("local = 1\n", &config).unwrap();
+    assert!(result.parse_errors[0].msg.starts_with("Syntax error: "));
+
+    let config = Config::from_json(r#"{"rules": {"no_such_rule": {}}}"#, std::path::Path::new("."));
+    let error = lint_source("", &config.unwrap()).unwrap_err();
+    assert_eq!(error, LintError::Config("Unknown rule: no_such_rule".to_string()));
+}
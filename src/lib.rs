@@ -1,3 +1,7 @@
+pub mod config;
+pub mod driver;
 pub mod lint;
 pub mod rules;
-pub mod trivial;
\ No newline at end of file
+pub mod trivial;
+
+pub use driver::{lint_source, LintError, LintResult};
@@ -1,3 +1,4 @@
+#[cfg(feature = "lua52")]
 use full_moon::ast::lua52::{Goto, Label};
 use full_moon::ast::span::ContainedSpan;
 
@@ -21,7 +22,7 @@ pub fn lint_ast<'a>(ast: &'a Ast, linter: &'a mut Linter) -> (Ast, LualintContex
     let ast = ast.clone();
     let mut ctx = LualintContext { linter };
     let new_block = lint_block(&mut ctx, ast.nodes());
-    let new_eof = lint_token_ref(&mut ctx, ast.eof());
+    let new_eof = lint_eof(&mut ctx, ast.eof());
 
     (ast.with_nodes(new_block).with_eof(new_eof), ctx)
 }
@@ -40,13 +41,26 @@ pub fn lint_token_ref(ctx: &mut LualintContext, token_ref: &TokenReference) -> T
         .linter
         .rule_registry
         .notify_enter(NodeKey::TokenRef, NW::TokenRef(token_ref.to_owned()));
+    let tok = ctx.linter.rule_registry.notify_leave(NodeKey::TokenRef, tok);
     must_match!(tok, NW::TokenRef)
 }
 
+pub fn lint_eof(ctx: &mut LualintContext, eof: &TokenReference) -> TokenReference {
+    let mut rt = NW::TokenRef(eof.to_owned());
+    rt = ctx.linter.rule_registry.notify_enter(NodeKey::Eof, rt);
+
+    let eof = lint_token_ref(ctx, &must_match!(rt, NW::TokenRef));
+
+    rt = ctx.linter.rule_registry.notify_leave(NodeKey::Eof, NW::TokenRef(eof));
+    must_match!(rt, NW::TokenRef)
+}
+
 pub fn lint_block(ctx: &mut LualintContext, block: &Block) -> Block {
     let mut blk_w = NW::Block(block.to_owned());
 
-    ctx.linter.rule_registry.trigger_walker(NodeKey::Block, WalkTy::Enter, blk_w);
+    blk_w = ctx.linter.rule_registry.trigger_walker(NodeKey::Block, WalkTy::Enter, blk_w);
+
+    let block = &must_match!(blk_w, NW::Block);
 
     let mut stmt_iterator = block.stmts_with_semicolon().peekable();
     let mut formatted_statements: Vec<(Stmt, Option<TokenReference>)> = Vec::new();
@@ -77,26 +91,29 @@ pub fn lint_block(ctx: &mut LualintContext, block: &Block) -> Block {
 }
 
 pub fn lint_last_stmt(ctx: &mut LualintContext, last_stmt: &LastStmt) -> LastStmt {
-    let last_stmt = match last_stmt {
-        LastStmt::Return(return_node) => lint_return(ctx, return_node),
-        LastStmt::Break(break_stmt) => lint_break(ctx, break_stmt),
-        _ => unreachable!("unimplemented last statement type: {:?}", last_stmt),
+    let mut last_stmt_w = NW::LastStmt(last_stmt.to_owned());
+    last_stmt_w = ctx.linter.rule_registry.notify_enter(NodeKey::LastStmt, last_stmt_w);
+
+    let last_stmt = match must_match!(last_stmt_w, NW::LastStmt) {
+        LastStmt::Return(return_node) => lint_return(ctx, &return_node),
+        LastStmt::Break(break_stmt) => lint_break(ctx, &break_stmt),
+        // `continue` only exists in Luau
+        other => other,
     };
-    let last_stmt_w = NW::LastStmt(last_stmt);
 
-    // match last_stmt_w {
-    // NW::LastStmt(last_stmt) => last_stmt,
-    // _ => unreachable!(),
-    // }
+    last_stmt_w = NW::LastStmt(last_stmt);
+    last_stmt_w = ctx.linter.rule_registry.notify_leave(NodeKey::LastStmt, last_stmt_w);
+
     must_match!(last_stmt_w, NW::LastStmt)
 }
 
 pub fn lint_return(ctx: &mut LualintContext, return_node: &Return) -> LastStmt {
     let mut ret_node_w = NW::Return(return_node.to_owned());
 
-    ctx.linter.rule_registry.trigger_walker(NodeKey::Return, WalkTy::Enter, ret_node_w);
+    ret_node_w =
+        ctx.linter.rule_registry.trigger_walker(NodeKey::Return, WalkTy::Enter, ret_node_w);
 
-    let return_node = return_node.to_owned();
+    let return_node = must_match!(ret_node_w, NW::Return);
     let return_token = return_node.token();
 
     let return_token = lint_token_ref(ctx, return_token);
@@ -126,50 +143,125 @@ pub fn lint_expr_block(ctx: &mut LualintContext, expression: &Expression) -> Exp
 }
 
 pub fn lint_expr(ctx: &mut LualintContext, expression: &Expression) -> Expression {
-    match expression {
+    let mut rt = NW::Expression(expression.to_owned());
+    rt = ctx.linter.rule_registry.notify_enter(NodeKey::ExprBlock, rt);
+
+    let expression = match must_match!(rt, NW::Expression) {
         Expression::BinaryOperator { lhs, binop, rhs } => Expression::BinaryOperator {
-            lhs: Box::new(lint_expr_block(ctx, lhs)),
-            binop: binop.to_owned(),
-            rhs: Box::new(lint_expr_block(ctx, rhs)),
+            lhs: Box::new(lint_expr_block(ctx, &lhs)),
+            binop,
+            rhs: Box::new(lint_expr_block(ctx, &rhs)),
         },
         Expression::Parentheses { contained, expression } => Expression::Parentheses {
-            contained: contained.to_owned(),
-            expression: Box::new(lint_expr_block(ctx, expression)),
+            contained: lint_contained_span(ctx, &contained),
+            expression: Box::new(lint_expr_block(ctx, &expression)),
         },
         Expression::UnaryOperator { unop, expression } => Expression::UnaryOperator {
-            unop: unop.to_owned(),
-            expression: Box::new(lint_expr_block(ctx, expression)),
+            unop,
+            expression: Box::new(lint_expr_block(ctx, &expression)),
         },
         Expression::Value { value } => Expression::Value {
-            value: Box::new(match &**value {
+            value: Box::new(match *value {
                 Value::Function((function_token, body)) => {
-                    let block = lint_block(ctx, body.block());
-                    Value::Function((function_token.to_owned(), body.to_owned().with_block(block)))
+                    let function_token = lint_token_ref(ctx, &function_token);
+                    Value::Function((function_token, lint_func_body(ctx, &body)))
                 }
                 Value::FunctionCall(func_call) => {
-                    Value::FunctionCall(lint_func_call_block(ctx, func_call))
+                    Value::FunctionCall(lint_func_call_block(ctx, &func_call))
                 }
                 Value::TableConstructor(table_constructor) => {
-                    Value::TableConstructor(lint_table_ctor(ctx, table_constructor))
+                    Value::TableConstructor(lint_table_ctor(ctx, &table_constructor))
                 }
                 Value::ParenthesesExpression(expression) => {
-                    Value::ParenthesesExpression(lint_expr_block(ctx, expression))
+                    Value::ParenthesesExpression(lint_expr_block(ctx, &expression))
                 }
-                // TODO: var?
-                value => value.to_owned(),
+                Value::Var(var) => Value::Var(lint_var(ctx, &var)),
+                Value::Number(token) => Value::Number(lint_token_ref(ctx, &token)),
+                Value::String(token) => Value::String(lint_token_ref(ctx, &token)),
+                Value::Symbol(token) => Value::Symbol(lint_token_ref(ctx, &token)),
+                // values of other dialects are kept as is
+                value => value,
             }),
         },
-        _ => unreachable!("unimplemented expression type: {:?}", expression),
+        // expressions of other dialects are kept as is
+        expression => expression,
+    };
+
+    rt = NW::Expression(expression);
+    rt = ctx.linter.rule_registry.notify_leave(NodeKey::ExprBlock, rt);
+
+    must_match!(rt, NW::Expression)
+}
+
+pub fn lint_var(ctx: &mut LualintContext, var: &Var) -> Var {
+    match var {
+        Var::Name(name) => Var::Name(lint_token_ref(ctx, name)),
+        Var::Expression(var_expr) => {
+            let prefix = lint_prefix(ctx, var_expr.prefix());
+            let suffixes = var_expr.suffixes().map(|suffix| lint_suffix(ctx, suffix)).collect();
+            Var::Expression(var_expr.to_owned().with_prefix(prefix).with_suffixes(suffixes))
+        }
+        other => other.to_owned(),
+    }
+}
+
+pub fn lint_prefix(ctx: &mut LualintContext, prefix: &Prefix) -> Prefix {
+    match prefix {
+        Prefix::Expression(expression) => Prefix::Expression(lint_expr_block(ctx, expression)),
+        Prefix::Name(name) => Prefix::Name(lint_token_ref(ctx, name)),
+        other => other.to_owned(),
+    }
+}
+
+pub fn lint_suffix(ctx: &mut LualintContext, suffix: &Suffix) -> Suffix {
+    match suffix {
+        Suffix::Call(call) => Suffix::Call(match call {
+            Call::AnonymousCall(function_args) => {
+                Call::AnonymousCall(lint_func_args_block(ctx, function_args))
+            }
+            Call::MethodCall(method_call) => {
+                let colon_token = lint_token_ref(ctx, method_call.colon_token());
+                let name = lint_token_ref(ctx, method_call.name());
+                let args = lint_func_args_block(ctx, method_call.args());
+                Call::MethodCall(
+                    method_call
+                        .to_owned()
+                        .with_colon_token(colon_token)
+                        .with_name(name)
+                        .with_args(args),
+                )
+            }
+            other => other.to_owned(),
+        }),
+        Suffix::Index(index) => Suffix::Index(match index {
+            Index::Brackets { brackets, expression } => Index::Brackets {
+                brackets: lint_contained_span(ctx, brackets),
+                expression: lint_expr_block(ctx, expression),
+            },
+            Index::Dot { dot, name } => {
+                Index::Dot { dot: lint_token_ref(ctx, dot), name: lint_token_ref(ctx, name) }
+            }
+            other => other.to_owned(),
+        }),
+        other => other.to_owned(),
     }
 }
 
 pub fn lint_break(ctx: &mut LualintContext, break_stmt: &TokenReference) -> LastStmt {
-    let break_stmt = lint_token_ref(ctx, break_stmt);
-    LastStmt::Break(break_stmt)
+    let mut rt = NW::TokenRef(break_stmt.to_owned());
+    rt = ctx.linter.rule_registry.notify_enter(NodeKey::Break, rt);
+
+    let break_stmt = lint_token_ref(ctx, &must_match!(rt, NW::TokenRef));
+
+    rt = ctx.linter.rule_registry.notify_leave(NodeKey::Break, NW::TokenRef(break_stmt));
+    LastStmt::Break(must_match!(rt, NW::TokenRef))
 }
 
 pub fn lint_stmt(ctx: &mut LualintContext, stmt: &Stmt) -> Stmt {
-    match stmt {
+    let mut rt = NW::Stmt(stmt.to_owned());
+    rt = ctx.linter.rule_registry.notify_enter(NodeKey::Stmt, rt);
+
+    let stmt = match &must_match!(rt, NW::Stmt) {
         Stmt::Assignment(assignment_stmt) => lint_assignment(ctx, assignment_stmt),
         Stmt::Do(do_stmt) => lint_do(ctx, do_stmt),
         Stmt::FunctionCall(func_call_stmt) => lint_func_call(ctx, func_call_stmt),
@@ -181,75 +273,64 @@ pub fn lint_stmt(ctx: &mut LualintContext, stmt: &Stmt) -> Stmt {
         Stmt::NumericFor(numeric_for_stmt) => lint_numeric_for(ctx, numeric_for_stmt),
         Stmt::Repeat(repeat_stmt) => lint_repeat(ctx, repeat_stmt),
         Stmt::While(while_stmt) => lint_while(ctx, while_stmt),
+        #[cfg(feature = "lua52")]
         Stmt::Goto(goto_stmt) => lint_goto(ctx, goto_stmt),
+        #[cfg(feature = "lua52")]
         Stmt::Label(label_stmt) => lint_label(ctx, label_stmt),
-        _ => unreachable!("unimplemented statement type: {:?}", stmt),
-    }
+        // statements of other dialects are kept as is
+        other => other.to_owned(),
+    };
+
+    rt = NW::Stmt(stmt);
+    rt = ctx.linter.rule_registry.notify_leave(NodeKey::Stmt, rt);
+
+    must_match!(rt, NW::Stmt)
 }
 
 pub fn lint_func_call_block(ctx: &mut LualintContext, func_call: &FunctionCall) -> FunctionCall {
-    let prefix = match func_call.prefix() {
-        Prefix::Expression(expression) => Prefix::Expression(lint_expr_block(ctx, expression)),
-        Prefix::Name(name) => Prefix::Name(name.to_owned()),
-        other => panic!("unknown node {other:?}"),
-    };
+    let mut rt = NW::FunctionCall(func_call.to_owned());
+    rt = ctx.linter.rule_registry.notify_enter(NodeKey::FuncCallBlock, rt);
 
-    let suffixes = func_call
-        .suffixes()
-        .map(|suffix| match suffix {
-            Suffix::Call(call) => Suffix::Call(match call {
-                Call::AnonymousCall(function_args) => {
-                    Call::AnonymousCall(lint_func_args_block(ctx, function_args))
-                }
-                Call::MethodCall(method_call) => {
-                    let args = lint_func_args_block(ctx, method_call.args());
-                    Call::MethodCall(method_call.to_owned().with_args(args))
-                }
-                other => panic!("unknown node {other:?}"),
-            }),
-            Suffix::Index(index) => Suffix::Index(match index {
-                Index::Brackets { brackets, expression } => Index::Brackets {
-                    brackets: brackets.to_owned(),
-                    expression: lint_expr_block(ctx, expression),
-                },
-                _ => index.to_owned(),
-            }),
-            other => panic!("unknown node {other:?}"),
-        })
-        .collect();
+    let func_call = must_match!(rt, NW::FunctionCall);
+    let prefix = lint_prefix(ctx, func_call.prefix());
+    let suffixes = func_call.suffixes().map(|suffix| lint_suffix(ctx, suffix)).collect();
+    let func_call = func_call.to_owned().with_prefix(prefix).with_suffixes(suffixes);
 
-    func_call.to_owned().with_prefix(prefix).with_suffixes(suffixes)
+    rt = NW::FunctionCall(func_call);
+    rt = ctx.linter.rule_registry.notify_leave(NodeKey::FuncCallBlock, rt);
+
+    must_match!(rt, NW::FunctionCall)
 }
 
 pub fn lint_func_args_block(
     ctx: &mut LualintContext,
     function_args: &FunctionArgs,
 ) -> FunctionArgs {
-    match function_args {
+    let mut rt = NW::FunctionArgs(function_args.to_owned());
+    rt = ctx.linter.rule_registry.notify_enter(NodeKey::FuncArgsBlock, rt);
+
+    let function_args = match must_match!(rt, NW::FunctionArgs) {
         FunctionArgs::Parentheses { parentheses, arguments } => FunctionArgs::Parentheses {
-            parentheses: parentheses.to_owned(),
-            arguments: arguments
-                .pairs()
-                .map(|pair| pair.to_owned().map(|expression| lint_expr_block(ctx, &expression)))
-                .collect(),
+            parentheses: lint_contained_span(ctx, &parentheses),
+            arguments: lint_punctuated(ctx, &arguments, lint_expr_block),
         },
         FunctionArgs::TableConstructor(table_constructor) => {
-            FunctionArgs::TableConstructor(lint_table_ctor(ctx, table_constructor))
+            FunctionArgs::TableConstructor(lint_table_ctor(ctx, &table_constructor))
         }
-        _ => function_args.to_owned(),
-    }
+        FunctionArgs::String(token) => FunctionArgs::String(lint_token_ref(ctx, &token)),
+        other => other,
+    };
+
+    rt = NW::FunctionArgs(function_args);
+    rt = ctx.linter.rule_registry.notify_leave(NodeKey::FuncArgsBlock, rt);
+
+    must_match!(rt, NW::FunctionArgs)
 }
 
 pub fn lint_table_ctor(
     ctx: &mut LualintContext,
     table_constructor: &TableConstructor,
 ) -> TableConstructor {
-    let fields = table_constructor
-        .fields()
-        .pairs()
-        .map(|pair| pair.to_owned().map(|pair| lint_field(ctx, pair)))
-        .collect();
-
     let node_w = NW::TableConstructor(table_constructor.to_owned());
 
     let node_w =
@@ -257,7 +338,12 @@ pub fn lint_table_ctor(
 
     let x = must_match!(node_w, NW::TableConstructor);
 
-    let node = x.with_fields(fields);
+    let fields =
+        x.fields().pairs().map(|pair| pair.to_owned().map(|pair| lint_field(ctx, pair))).collect();
+
+    let braces = lint_contained_span(ctx, x.braces());
+
+    let node = x.with_braces(braces).with_fields(fields);
 
     let node_w = ctx.linter.rule_registry.trigger_walker(
         NodeKey::TableConstructor,
@@ -269,34 +355,78 @@ pub fn lint_table_ctor(
 }
 
 pub fn lint_field(ctx: &mut LualintContext, field: Field) -> Field {
-    match field {
+    let mut rt = NW::Field(field);
+    rt = ctx.linter.rule_registry.notify_enter(NodeKey::Field, rt);
+
+    let field = match must_match!(rt, NW::Field) {
         Field::ExpressionKey { brackets, key, equal, value } => Field::ExpressionKey {
-            brackets,
+            brackets: lint_contained_span(ctx, &brackets),
             key: lint_expr_block(ctx, &key),
-            equal,
+            equal: lint_token_ref(ctx, &equal),
+            value: lint_expr_block(ctx, &value),
+        },
+        Field::NameKey { key, equal, value } => Field::NameKey {
+            key: lint_token_ref(ctx, &key),
+            equal: lint_token_ref(ctx, &equal),
             value: lint_expr_block(ctx, &value),
         },
-        Field::NameKey { key, equal, value } => {
-            Field::NameKey { key, equal, value: lint_expr_block(ctx, &value) }
-        }
         Field::NoKey(expression) => Field::NoKey(lint_expr_block(ctx, &expression)),
-        other => panic!("unknown node {other:?}"),
-    }
+        other => other,
+    };
+
+    rt = NW::Field(field);
+    rt = ctx.linter.rule_registry.notify_leave(NodeKey::Field, rt);
+
+    must_match!(rt, NW::Field)
 }
 
-// todo: lint_assignment
-pub fn lint_assignment(_ctx: &mut LualintContext, assignment_stmt: &Assignment) -> Stmt {
-    Stmt::Assignment(assignment_stmt.to_owned())
+pub fn lint_assignment(ctx: &mut LualintContext, assignment_stmt: &Assignment) -> Stmt {
+    let mut rt = NW::Assignment(assignment_stmt.to_owned());
+    rt = ctx.linter.rule_registry.notify_enter(NodeKey::Assignment, rt);
+
+    let assignment_stmt = must_match!(rt, NW::Assignment);
+    let var_list = lint_punctuated(ctx, assignment_stmt.variables(), lint_var);
+    let equal_token = lint_token_ref(ctx, assignment_stmt.equal_token());
+    let expr_list = lint_punctuated(ctx, assignment_stmt.expressions(), lint_expr);
+
+    let assignment_stmt = assignment_stmt
+        .with_variables(var_list)
+        .with_equal_token(equal_token)
+        .with_expressions(expr_list);
+
+    rt = NW::Assignment(assignment_stmt);
+    rt = ctx.linter.rule_registry.notify_leave(NodeKey::Assignment, rt);
+
+    Stmt::Assignment(must_match!(rt, NW::Assignment))
 }
 
-// todo: lint_do
-pub fn lint_do(_ctx: &mut LualintContext, do_stmt: &Do) -> Stmt {
-    Stmt::Do(do_stmt.to_owned())
+pub fn lint_do(ctx: &mut LualintContext, do_stmt: &Do) -> Stmt {
+    let mut rt = NW::Do(do_stmt.to_owned());
+    rt = ctx.linter.rule_registry.notify_enter(NodeKey::Do, rt);
+
+    let do_stmt = must_match!(rt, NW::Do);
+    let do_token = lint_token_ref(ctx, do_stmt.do_token());
+    let block = lint_block(ctx, do_stmt.block());
+    let end_token = lint_token_ref(ctx, do_stmt.end_token());
+
+    let do_stmt = do_stmt.with_do_token(do_token).with_block(block).with_end_token(end_token);
+
+    rt = NW::Do(do_stmt);
+    rt = ctx.linter.rule_registry.notify_leave(NodeKey::Do, rt);
+
+    Stmt::Do(must_match!(rt, NW::Do))
 }
 
-// todo: lint_func_call
-pub fn lint_func_call(_ctx: &mut LualintContext, func_call_stmt: &FunctionCall) -> Stmt {
-    Stmt::FunctionCall(func_call_stmt.to_owned())
+pub fn lint_func_call(ctx: &mut LualintContext, func_call_stmt: &FunctionCall) -> Stmt {
+    let mut rt = NW::FunctionCall(func_call_stmt.to_owned());
+    rt = ctx.linter.rule_registry.notify_enter(NodeKey::FuncCall, rt);
+
+    let func_call_stmt = lint_func_call_block(ctx, &must_match!(rt, NW::FunctionCall));
+
+    rt = NW::FunctionCall(func_call_stmt);
+    rt = ctx.linter.rule_registry.notify_leave(NodeKey::FuncCall, rt);
+
+    Stmt::FunctionCall(must_match!(rt, NW::FunctionCall))
 }
 
 pub fn lint_func_decl(ctx: &mut LualintContext, func_decl_stmt: &FunctionDeclaration) -> Stmt {
@@ -342,10 +472,31 @@ pub fn lint_func_name(ctx: &mut LualintContext, func_name: &FunctionName) -> Fun
 }
 
 pub fn lint_generic_for(ctx: &mut LualintContext, generic_for_stmt: &GenericFor) -> Stmt {
-    // return Stmt::GenericFor(generic_for_stmt.to_owned());
     let mut rt = NW::GenericFor(generic_for_stmt.to_owned());
     rt = ctx.linter.rule_registry.notify_enter(NodeKey::GenericFor, rt);
 
+    let generic_for_stmt = must_match!(rt, NW::GenericFor);
+    let for_token = lint_token_ref(ctx, generic_for_stmt.for_token());
+    let names = lint_punctuated(ctx, generic_for_stmt.names(), lint_token_ref);
+    let in_token = lint_token_ref(ctx, generic_for_stmt.in_token());
+    let expr_list = lint_punctuated(ctx, generic_for_stmt.expressions(), lint_expr);
+    let do_token = lint_token_ref(ctx, generic_for_stmt.do_token());
+    let block = lint_block(ctx, generic_for_stmt.block());
+    let end_token = lint_token_ref(ctx, generic_for_stmt.end_token());
+
+    let generic_for_stmt = generic_for_stmt
+        .to_owned()
+        .with_for_token(for_token)
+        .with_names(names)
+        .with_in_token(in_token)
+        .with_expressions(expr_list)
+        .with_do_token(do_token)
+        .with_block(block)
+        .with_end_token(end_token);
+
+    rt = NW::GenericFor(generic_for_stmt);
+    rt = ctx.linter.rule_registry.notify_leave(NodeKey::GenericFor, rt);
+
     Stmt::GenericFor(must_match!(rt, NW::GenericFor))
 }
 
@@ -408,18 +559,27 @@ pub fn lint_if(ctx: &mut LualintContext, if_stmt: &If) -> Stmt {
 // assign: var a = b;
 // decl: var a;
 pub fn lint_local_assign(ctx: &mut LualintContext, las: &LocalAssignment) -> Stmt {
-    // TODO: Lint the local assignment
-    let name_list = las.names().to_owned();
+    let mut rt = NW::LocalAssignment(las.to_owned());
+    rt = ctx.linter.rule_registry.notify_enter(NodeKey::LocalAssign, rt);
+
+    let las = must_match!(rt, NW::LocalAssignment);
+    let local_token = lint_token_ref(ctx, las.local_token());
+    let name_list = lint_punctuated(ctx, las.names(), lint_token_ref);
+    let equal_token = las.equal_token().map(|equal_token| lint_token_ref(ctx, equal_token));
     let expr_list = lint_punctuated(ctx, las.expressions(), lint_expr);
-    let equal_token = las.equal_token().to_owned();
-    let local_token = las.local_token().to_owned();
-    let local_assignment = LocalAssignment::new(name_list);
-    Stmt::LocalAssignment(
-        local_assignment
-            .with_local_token(local_token)
-            .with_equal_token(equal_token.to_owned().cloned())
-            .with_expressions(expr_list),
-    )
+
+    // keep the node itself so that attributes of newer dialects survive
+    let las = las
+        .to_owned()
+        .with_local_token(local_token)
+        .with_names(name_list)
+        .with_equal_token(equal_token)
+        .with_expressions(expr_list);
+
+    rt = NW::LocalAssignment(las);
+    rt = ctx.linter.rule_registry.notify_leave(NodeKey::LocalAssign, rt);
+
+    Stmt::LocalAssignment(must_match!(rt, NW::LocalAssignment))
 }
 
 pub fn lint_punctuated<T, F>(
@@ -452,7 +612,10 @@ where
 }
 
 pub fn lint_local_func(ctx: &mut LualintContext, local_func_stmt: &LocalFunction) -> Stmt {
-    // Calculate trivia
+    let mut rt = NW::LocalFunction(local_func_stmt.to_owned());
+    rt = ctx.linter.rule_registry.notify_enter(NodeKey::LocalFunc, rt);
+
+    let local_func_stmt = must_match!(rt, NW::LocalFunction);
     let local_token = lint_token_ref(ctx, local_func_stmt.local_token());
     let function_token = lint_token_ref(ctx, local_func_stmt.function_token());
     let formatted_name = lint_token_ref(ctx, local_func_stmt.name());
@@ -463,7 +626,11 @@ pub fn lint_local_func(ctx: &mut LualintContext, local_func_stmt: &LocalFunction
         .with_local_token(local_token)
         .with_function_token(function_token)
         .with_body(func_body);
-    Stmt::LocalFunction(f)
+
+    rt = NW::LocalFunction(f);
+    rt = ctx.linter.rule_registry.notify_leave(NodeKey::LocalFunc, rt);
+
+    Stmt::LocalFunction(must_match!(rt, NW::LocalFunction))
 }
 
 /// Formats a FunctionBody node
@@ -607,6 +774,7 @@ pub fn lint_while(ctx: &mut LualintContext, while_stmt: &While) -> Stmt {
     Stmt::While(rt)
 }
 
+#[cfg(feature = "lua52")]
 pub fn lint_goto(ctx: &mut LualintContext, goto_stmt: &Goto) -> Stmt {
     // Stmt::Goto(goto_stmt.to_owned())
     let mut rt = NW::Goto(goto_stmt.to_owned());
@@ -627,6 +795,27 @@ pub fn lint_goto(ctx: &mut LualintContext, goto_stmt: &Goto) -> Stmt {
     Stmt::Goto(rt)
 }
 
-pub fn lint_label(_ctx: &mut LualintContext, label_stmt: &Label) -> Stmt {
-    Stmt::Label(label_stmt.to_owned())
+#[cfg(feature = "lua52")]
+pub fn lint_label(ctx: &mut LualintContext, label_stmt: &Label) -> Stmt {
+    let mut rt = NW::Label(label_stmt.to_owned());
+    rt = ctx.linter.rule_registry.trigger_walker(NodeKey::Label, WalkTy::Enter, rt);
+
+    let label_stmt = must_match!(rt, NW::Label);
+    let left_colons = lint_token_ref(ctx, label_stmt.left_colons());
+    let name = lint_token_ref(ctx, label_stmt.name());
+    let right_colons = lint_token_ref(ctx, label_stmt.right_colons());
+
+    let label_stmt = label_stmt
+        .to_owned()
+        .with_left_colons(left_colons)
+        .with_name(name)
+        .with_right_colons(right_colons);
+
+    rt = NW::Label(label_stmt);
+
+    rt = ctx.linter.rule_registry.trigger_walker(NodeKey::Label, WalkTy::Leave, rt);
+
+    let rt = must_match!(rt, NW::Label);
+
+    Stmt::Label(rt)
 }
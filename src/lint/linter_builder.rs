@@ -1,24 +1,65 @@
-use crate::rules::{Registry, Rule, RuleContext};
+use linked_hash_map::LinkedHashMap;
 
-use super::Linter;
+use crate::rules::{self, Registry, ReportLevel, Rule, RuleContext};
+
+use super::{Linter, LuaVersion};
 
 #[derive(Default)]
 pub struct LinterBuilder {
     rule_registry: Registry,
+    lua_version: LuaVersion,
+    severities: LinkedHashMap<String, ReportLevel>,
 }
 
 impl LinterBuilder {
+    pub fn with_lua_version(mut self, lua_version: LuaVersion) -> Self {
+        self.lua_version = lua_version;
+        self
+    }
+
+    pub fn with_severity(mut self, rule_name: &str, level: ReportLevel) -> Self {
+        self.severities.insert(rule_name.to_string(), level);
+        self
+    }
 
+    /// Adds the rule `T` under `rule_name`. `Null` stands for no options.
+    ///
+    /// # Panics
+    ///
+    /// If a rule registered as `rule_name` does not accept `rule_config`, see
+    /// `with_rule_by_name` for configs written by users.
     pub fn with_rule<T>(mut self, rule_name: &str, rule_config: &serde_json::Value) -> Self
     where
         T: RuleContext + Rule,
     {
-        let rule = T::apply(&mut self.rule_registry, rule_config);
-        self.rule_registry.bind_ctx(rule_name, Box::new(rule));
+        if let Some(info) = rules::find_rule(rule_name).filter(|_| !rule_config.is_null()) {
+            info.validate(rule_config).unwrap_or_else(|e| panic!("{e}"));
+        }
+        self.rule_registry.add_rule(rule_name, rules::create_rule::<T>, rule_config);
         self
     }
 
+    /// Adds a rule registered in `rules::ALL_RULES`, built-in or added with
+    /// `rules::register_rule`, after checking `rule_config` against its options.
+    pub fn with_rule_by_name(
+        mut self,
+        rule_name: &str,
+        rule_config: &serde_json::Value,
+    ) -> Result<Self, String> {
+        let info =
+            rules::find_rule(rule_name).ok_or_else(|| format!("Unknown rule: {rule_name}"))?;
+        info.validate(rule_config)?;
+        self.rule_registry.add_rule(rule_name, info.create, rule_config);
+        Ok(self)
+    }
+
     pub fn build(self) -> Linter {
-        Linter { rule_registry: self.rule_registry }
+        Linter {
+            rule_registry: self.rule_registry,
+            lua_version: self.lua_version,
+            suppressions: Default::default(),
+            report_unused_suppressions: false,
+            severities: self.severities,
+        }
     }
 }
@@ -0,0 +1,137 @@
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "lua53")]
+use full_moon::ast::{BinOp, Expression, UnOp};
+use full_moon::{ast::Ast, tokenizer::TokenReference, visitors::Visitor};
+
+use crate::rules::{LintReport, ReportLevel};
+
+/// The Lua dialect the linted code targets. The parser accepts every dialect
+/// enabled at compile time, syntax the target lacks is reported instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LuaVersion {
+    Lua51,
+    Lua52,
+    Lua53,
+    Lua54,
+    #[default]
+    LuaJIT,
+}
+
+impl LuaVersion {
+    pub fn has_goto(self) -> bool {
+        !matches!(self, LuaVersion::Lua51)
+    }
+
+    // `//` came together with the bitwise operators in 5.3
+    pub fn has_integer_ops(self) -> bool {
+        matches!(self, LuaVersion::Lua53 | LuaVersion::Lua54)
+    }
+
+    pub fn has_attributes(self) -> bool {
+        matches!(self, LuaVersion::Lua54)
+    }
+}
+
+impl FromStr for LuaVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "5.1" | "lua51" => Ok(LuaVersion::Lua51),
+            "5.2" | "lua52" => Ok(LuaVersion::Lua52),
+            "5.3" | "lua53" => Ok(LuaVersion::Lua53),
+            "5.4" | "lua54" => Ok(LuaVersion::Lua54),
+            "luajit" => Ok(LuaVersion::LuaJIT),
+            _ => Err(format!(
+                "unknown Lua version `{s}`, expected one of 5.1, 5.2, 5.3, 5.4 or luajit"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LuaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LuaVersion::Lua51 => write!(f, "Lua 5.1"),
+            LuaVersion::Lua52 => write!(f, "Lua 5.2"),
+            LuaVersion::Lua53 => write!(f, "Lua 5.3"),
+            LuaVersion::Lua54 => write!(f, "Lua 5.4"),
+            LuaVersion::LuaJIT => write!(f, "LuaJIT"),
+        }
+    }
+}
+
+/// Reports the syntax in `ast` that `version` does not support.
+pub fn check_syntax(ast: &Ast, version: LuaVersion) -> Vec<LintReport> {
+    let mut checker = SyntaxChecker { version, reports: vec![] };
+    checker.visit_ast(ast);
+    checker.reports
+}
+
+// without `lua52` the parser only knows Lua 5.1, so there is nothing to check
+#[cfg_attr(not(feature = "lua52"), allow(dead_code))]
+struct SyntaxChecker {
+    version: LuaVersion,
+    reports: Vec<LintReport>,
+}
+
+#[cfg_attr(not(feature = "lua52"), allow(dead_code))]
+impl SyntaxChecker {
+    fn report(&mut self, token: &TokenReference, what: &str) {
+        self.reports.push(LintReport {
+            pos: token.start_position().into(),
+            level: ReportLevel::Error,
+            msg: format!("Syntax error: {} not supported by {}", what, self.version),
+            fix: None,
+        });
+    }
+}
+
+impl Visitor for SyntaxChecker {
+    #[cfg(feature = "lua52")]
+    fn visit_goto(&mut self, node: &full_moon::ast::lua52::Goto) {
+        if !self.version.has_goto() {
+            self.report(node.goto_token(), "`goto` statements are");
+        }
+    }
+
+    #[cfg(feature = "lua52")]
+    fn visit_label(&mut self, node: &full_moon::ast::lua52::Label) {
+        if !self.version.has_goto() {
+            self.report(node.left_colons(), "labels are");
+        }
+    }
+
+    #[cfg(feature = "lua53")]
+    fn visit_expression(&mut self, node: &Expression) {
+        if self.version.has_integer_ops() {
+            return;
+        }
+        match node {
+            Expression::BinaryOperator { binop: BinOp::DoubleSlash(token), .. } => {
+                self.report(token, "the `//` operator is")
+            }
+            Expression::BinaryOperator {
+                binop:
+                    binop @ (BinOp::Ampersand(_)
+                    | BinOp::Pipe(_)
+                    | BinOp::Tilde(_)
+                    | BinOp::DoubleLessThan(_)
+                    | BinOp::DoubleGreaterThan(_)),
+                ..
+            } => self.report(binop.token(), "bitwise operators are"),
+            Expression::UnaryOperator { unop: UnOp::Tilde(token), .. } => {
+                self.report(token, "bitwise operators are")
+            }
+            _ => {}
+        }
+    }
+
+    #[cfg(feature = "lua54")]
+    fn visit_attribute(&mut self, node: &full_moon::ast::lua54::Attribute) {
+        if !self.version.has_attributes() {
+            self.report(node.brackets().tokens().0, "variable attributes are");
+        }
+    }
+}
@@ -1,13 +1,28 @@
 use full_moon::tokenizer::Token;
+use linked_hash_map::LinkedHashMap;
 
-use crate::rules::Registry;
+use crate::rules::{Registry, ReportLevel};
 
 #[derive(Default)]
 pub struct Linter {
     pub rule_registry: Registry,
+    pub lua_version: LuaVersion,
+    /// suppression comments of the file being linted, collected by the driver
+    pub suppressions: Suppressions,
+    /// whether `driver::collect_reports` lists the suppressions that silenced nothing
+    pub report_unused_suppressions: bool,
+    /// levels configured with the `severity` option, overriding the rules' own
+    pub severities: LinkedHashMap<String, ReportLevel>,
 }
 
-impl Linter {}
+impl Linter {
+    /// Forgets everything about the last linted file, see `Registry::begin_file`.
+    /// `driver::drive` calls it, so that a linter can be reused for many files.
+    pub fn begin_file(&mut self) {
+        self.rule_registry.begin_file();
+        self.suppressions = Suppressions::default();
+    }
+}
 
 pub struct LualintContext<'a> {
     pub linter: &'a mut Linter,
@@ -15,8 +30,14 @@ pub struct LualintContext<'a> {
 
 pub mod lint_visitor;
 pub mod linter_builder;
+pub mod lua_version;
+pub mod scope;
+pub mod suppression;
 
 pub type LinterBuilder = linter_builder::LinterBuilder;
+pub type LuaVersion = lua_version::LuaVersion;
+pub type ScopeAnalysis = scope::ScopeAnalysis;
+pub type Suppressions = suppression::Suppressions;
 
 pub fn lint_tokens(tokens: &Vec<Token>, linter: &mut Linter) -> Vec<Token> {
     let mut new_tokens: Vec<Token> = Vec::new();
@@ -0,0 +1,555 @@
+use full_moon::{
+    ast::{punctuated::Punctuated, *},
+    tokenizer::TokenReference,
+};
+
+use crate::rules::Pos;
+
+pub type ScopeId = usize;
+pub type VariableId = usize;
+pub type ReferenceId = usize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// the chunk itself, its locals are upvalues of every function in it
+    File,
+    /// a function body, holding its parameters and the locals of its block
+    Function,
+    /// `do`, `if`, `while`, `repeat` and `for` blocks
+    Block,
+}
+
+#[derive(Clone, Debug)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub parent: Option<ScopeId>,
+    /// the variables declared directly in this scope, in order
+    pub variables: Vec<VariableId>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VariableKind {
+    Local,
+    LocalFunction,
+    Parameter,
+    /// the implicit `self` of `function t:method()`
+    SelfParameter,
+    /// `...`, read as a value in the function body
+    Vararg,
+    ForVariable,
+}
+
+/// A declared name.
+#[derive(Clone, Debug)]
+pub struct Variable {
+    pub name: String,
+    pub kind: VariableKind,
+    /// where the name is declared
+    pub pos: Pos,
+    pub scope: ScopeId,
+    /// the variable of the same name visible where this one is declared, which
+    /// it hides from there on
+    pub shadowed: Option<VariableId>,
+    pub references: Vec<ReferenceId>,
+    /// the name the variable is initialized with, e.g. `ngx` in `local ngx = ngx`
+    pub alias_of: Option<ReferenceId>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// A use of a name, e.g. `a` in `a = 1`, `print(a)` or `a.b.c = 1` (a read).
+#[derive(Clone, Debug)]
+pub struct Reference {
+    pub name: String,
+    pub pos: Pos,
+    pub access: Access,
+    pub scope: ScopeId,
+    /// the declaration the name refers to, `None` for globals
+    pub variable: Option<VariableId>,
+    /// whether the variable is declared outside of the function the reference is in
+    pub upvalue: bool,
+    /// the field indexed right after the name, `b` in `a.b.c` or `a.b()`
+    pub field: Option<FieldAccess>,
+}
+
+/// A field of a referenced name, e.g. `b` in `a.b` (a read) or `a.b = 1` (a write).
+#[derive(Clone, Debug)]
+pub struct FieldAccess {
+    pub name: String,
+    pub pos: Pos,
+    pub access: Access,
+}
+
+/// The scopes, declarations and references of a file. Rules receive it with
+/// `Registry::listen_scopes` before the AST is walked.
+#[derive(Clone, Debug, Default)]
+pub struct ScopeAnalysis {
+    /// the file scope comes first
+    pub scopes: Vec<Scope>,
+    pub variables: Vec<Variable>,
+    pub references: Vec<Reference>,
+}
+
+impl ScopeAnalysis {
+    pub fn from_ast(ast: &Ast) -> Self {
+        let mut analyzer = Analyzer { analysis: ScopeAnalysis::default(), current: 0 };
+        analyzer.push_scope(ScopeKind::File);
+        analyzer.walk_block(ast.nodes());
+        analyzer.analysis
+    }
+
+    /// The references to `variable` that read it.
+    pub fn reads(&self, variable: VariableId) -> impl Iterator<Item = &Reference> {
+        self.variables[variable]
+            .references
+            .iter()
+            .map(|&reference| &self.references[reference])
+            .filter(|reference| reference.access == Access::Read)
+    }
+
+    /// The references to names that are not declared, in order.
+    pub fn globals(&self) -> impl Iterator<Item = &Reference> {
+        self.references.iter().filter(|reference| reference.variable.is_none())
+    }
+
+    /// The declaration `reference` refers to, `None` for globals.
+    pub fn declaration(&self, reference: &Reference) -> Option<&Variable> {
+        reference.variable.map(|variable| &self.variables[variable])
+    }
+
+    /// The reference at `line`:`col`, as in the positions of the reports.
+    pub fn reference_at(&self, line: usize, col: usize) -> Option<&Reference> {
+        self.references
+            .iter()
+            .find(|reference| reference.pos.line == line && reference.pos.col == col)
+    }
+
+    /// The function (or file) scope `scope` belongs to.
+    pub fn function_of(&self, scope: ScopeId) -> ScopeId {
+        let mut scope = scope;
+        while self.scopes[scope].kind == ScopeKind::Block {
+            scope = self.scopes[scope].parent.unwrap();
+        }
+        scope
+    }
+}
+
+struct Analyzer {
+    analysis: ScopeAnalysis,
+    current: ScopeId,
+}
+
+impl Analyzer {
+    fn push_scope(&mut self, kind: ScopeKind) {
+        let parent = self.analysis.scopes.len().checked_sub(1).map(|_| self.current);
+        self.analysis.scopes.push(Scope { kind, parent, variables: vec![] });
+        self.current = self.analysis.scopes.len() - 1;
+    }
+
+    fn pop_scope(&mut self) {
+        self.current = self.analysis.scopes[self.current].parent.unwrap();
+    }
+
+    /// The variable `name` refers to in the current scope, the latest
+    /// declaration of the innermost scope winning.
+    fn resolve(&self, name: &str) -> Option<VariableId> {
+        let mut scope = Some(self.current);
+        while let Some(id) = scope {
+            let found = self.analysis.scopes[id]
+                .variables
+                .iter()
+                .rev()
+                .find(|&&variable| self.analysis.variables[variable].name == name);
+            if let Some(&variable) = found {
+                return Some(variable);
+            }
+            scope = self.analysis.scopes[id].parent;
+        }
+        None
+    }
+
+    fn declare(&mut self, name: &str, pos: Pos, kind: VariableKind) {
+        let shadowed = self.resolve(name);
+        let id = self.analysis.variables.len();
+        self.analysis.variables.push(Variable {
+            name: name.to_string(),
+            kind,
+            pos,
+            scope: self.current,
+            shadowed,
+            references: vec![],
+            alias_of: None,
+        });
+        self.analysis.scopes[self.current].variables.push(id);
+    }
+
+    fn declare_token(&mut self, token: &TokenReference, kind: VariableKind) {
+        self.declare(&token.token().to_string(), token.start_position().into(), kind);
+    }
+
+    fn reference(&mut self, token: &TokenReference, access: Access) -> ReferenceId {
+        let name = token.token().to_string();
+        let variable = self.resolve(&name);
+        let upvalue = variable.is_some_and(|variable| {
+            let declared_in = self.analysis.variables[variable].scope;
+            self.analysis.function_of(declared_in) != self.analysis.function_of(self.current)
+        });
+        let id = self.analysis.references.len();
+        self.analysis.references.push(Reference {
+            name,
+            pos: token.start_position().into(),
+            access,
+            scope: self.current,
+            variable,
+            upvalue,
+            field: None,
+        });
+        if let Some(variable) = variable {
+            self.analysis.variables[variable].references.push(id);
+        }
+        id
+    }
+
+    fn index_field(&mut self, reference: ReferenceId, name: &TokenReference, access: Access) {
+        self.analysis.references[reference].field = Some(FieldAccess {
+            name: name.token().to_string(),
+            pos: name.start_position().into(),
+            access,
+        });
+    }
+
+    fn walk_scoped_block(&mut self, block: &Block) {
+        self.push_scope(ScopeKind::Block);
+        self.walk_block(block);
+        self.pop_scope();
+    }
+
+    fn walk_block(&mut self, block: &Block) {
+        for stmt in block.stmts() {
+            self.walk_stmt(stmt);
+        }
+        if let Some(LastStmt::Return(return_node)) = block.last_stmt() {
+            self.walk_exprs(return_node.returns());
+        }
+    }
+
+    fn walk_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Assignment(assignment) => {
+                self.walk_exprs(assignment.expressions());
+                for var in assignment.variables() {
+                    match var {
+                        Var::Name(name) => {
+                            self.reference(name, Access::Write);
+                        }
+                        Var::Expression(var_expr) if var_expr.suffixes().count() == 1 => {
+                            // `a.b = 1` writes the field `b`
+                            self.walk_suffixed(
+                                var_expr.prefix(),
+                                var_expr.suffixes(),
+                                Access::Write,
+                            )
+                        }
+                        var => self.walk_var(var),
+                    }
+                }
+            }
+            Stmt::Do(do_stmt) => self.walk_scoped_block(do_stmt.block()),
+            Stmt::FunctionCall(func_call) => self.walk_func_call(func_call),
+            Stmt::FunctionDeclaration(func_decl) => {
+                let name = func_decl.name();
+                let mut names = name.names().iter();
+                let is_field = name.names().len() > 1 || name.method_name().is_some();
+                if let Some(first) = names.next() {
+                    // `function a.b()` reads `a`, `function a()` assigns it
+                    let access = if is_field { Access::Read } else { Access::Write };
+                    let reference = self.reference(first, access);
+                    if let Some(field) = names.next() {
+                        let written = name.names().len() == 2 && name.method_name().is_none();
+                        let access = if written { Access::Write } else { Access::Read };
+                        self.index_field(reference, field, access);
+                    } else if let Some(method) = name.method_name() {
+                        self.index_field(reference, method, Access::Write);
+                    }
+                }
+                self.walk_func_body(func_decl.body(), name.method_colon());
+            }
+            Stmt::GenericFor(generic_for) => {
+                self.walk_exprs(generic_for.expressions());
+                // the loop variables get a scope of their own around the body's,
+                // so that `local k = k` in the body hides them rather than redeclares
+                self.push_scope(ScopeKind::Block);
+                for name in generic_for.names() {
+                    self.declare_token(name, VariableKind::ForVariable);
+                }
+                self.walk_scoped_block(generic_for.block());
+                self.pop_scope();
+            }
+            Stmt::If(if_stmt) => {
+                self.walk_expr(if_stmt.condition());
+                self.walk_scoped_block(if_stmt.block());
+                for else_if in if_stmt.else_if().into_iter().flatten() {
+                    self.walk_expr(else_if.condition());
+                    self.walk_scoped_block(else_if.block());
+                }
+                if let Some(else_block) = if_stmt.else_block() {
+                    self.walk_scoped_block(else_block);
+                }
+            }
+            Stmt::LocalAssignment(local_assign) => {
+                // `local a = a` reads the outer `a`
+                let mut aliases = vec![];
+                for expression in local_assign.expressions() {
+                    let is_name = match expression {
+                        Expression::Value { value, .. } => {
+                            matches!(&**value, Value::Var(Var::Name(_)))
+                        }
+                        _ => false,
+                    };
+                    aliases.push(Some(self.analysis.references.len()).filter(|_| is_name));
+                    self.walk_expr(expression);
+                }
+                for (i, name) in local_assign.names().iter().enumerate() {
+                    self.declare_token(name, VariableKind::Local);
+                    let variable = self.analysis.variables.last_mut().unwrap();
+                    variable.alias_of = aliases.get(i).copied().flatten();
+                }
+            }
+            Stmt::LocalFunction(local_func) => {
+                // declared before its body, so that it can call itself
+                self.declare_token(local_func.name(), VariableKind::LocalFunction);
+                self.walk_func_body(local_func.body(), None);
+            }
+            Stmt::NumericFor(numeric_for) => {
+                self.walk_expr(numeric_for.start());
+                self.walk_expr(numeric_for.end());
+                if let Some(step) = numeric_for.step() {
+                    self.walk_expr(step);
+                }
+                self.push_scope(ScopeKind::Block);
+                self.declare_token(numeric_for.index_variable(), VariableKind::ForVariable);
+                self.walk_scoped_block(numeric_for.block());
+                self.pop_scope();
+            }
+            Stmt::Repeat(repeat_stmt) => {
+                // the condition sees the locals of the block
+                self.push_scope(ScopeKind::Block);
+                self.walk_block(repeat_stmt.block());
+                self.walk_expr(repeat_stmt.until());
+                self.pop_scope();
+            }
+            Stmt::While(while_stmt) => {
+                self.walk_expr(while_stmt.condition());
+                self.walk_scoped_block(while_stmt.block());
+            }
+            // `goto`, labels and statements of other dialects declare and use no variables
+            _ => {}
+        }
+    }
+
+    /// `method_colon` is set for `function t:method()`, which declares `self`.
+    fn walk_func_body(&mut self, body: &FunctionBody, method_colon: Option<&TokenReference>) {
+        self.push_scope(ScopeKind::Function);
+        if let Some(colon) = method_colon {
+            self.declare("self", colon.start_position().into(), VariableKind::SelfParameter);
+        }
+        for parameter in body.parameters() {
+            match parameter {
+                Parameter::Name(name) => self.declare_token(name, VariableKind::Parameter),
+                Parameter::Ellipse(vararg) => self.declare_token(vararg, VariableKind::Vararg),
+                _ => {}
+            }
+        }
+        self.walk_block(body.block());
+        self.pop_scope();
+    }
+
+    fn walk_exprs(&mut self, expressions: &Punctuated<Expression>) {
+        for expression in expressions {
+            self.walk_expr(expression);
+        }
+    }
+
+    fn walk_expr(&mut self, expression: &Expression) {
+        match expression {
+            Expression::BinaryOperator { lhs, rhs, .. } => {
+                self.walk_expr(lhs);
+                self.walk_expr(rhs);
+            }
+            Expression::Parentheses { expression, .. }
+            | Expression::UnaryOperator { expression, .. } => self.walk_expr(expression),
+            Expression::Value { value, .. } => match &**value {
+                Value::Function((_, body)) => self.walk_func_body(body, None),
+                Value::FunctionCall(func_call) => self.walk_func_call(func_call),
+                Value::TableConstructor(table) => self.walk_table(table),
+                Value::ParenthesesExpression(expression) => self.walk_expr(expression),
+                Value::Var(var) => self.walk_var(var),
+                Value::Symbol(symbol) if symbol.token().to_string() == "..." => {
+                    self.reference(symbol, Access::Read);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn walk_var(&mut self, var: &Var) {
+        match var {
+            Var::Name(name) => {
+                self.reference(name, Access::Read);
+            }
+            Var::Expression(var_expr) => {
+                self.walk_suffixed(var_expr.prefix(), var_expr.suffixes(), Access::Read)
+            }
+            _ => {}
+        }
+    }
+
+    fn walk_func_call(&mut self, func_call: &FunctionCall) {
+        self.walk_suffixed(func_call.prefix(), func_call.suffixes(), Access::Read)
+    }
+
+    /// `field_access` is how the first suffix is used when it indexes a field.
+    fn walk_suffixed<'a>(
+        &mut self,
+        prefix: &Prefix,
+        mut suffixes: impl Iterator<Item = &'a Suffix>,
+        field_access: Access,
+    ) {
+        match prefix {
+            Prefix::Expression(expression) => self.walk_expr(expression),
+            Prefix::Name(name) => {
+                let reference = self.reference(name, Access::Read);
+                match suffixes.next() {
+                    Some(Suffix::Index(Index::Dot { name, .. })) => {
+                        self.index_field(reference, name, field_access)
+                    }
+                    Some(Suffix::Call(Call::MethodCall(method_call))) => {
+                        self.index_field(reference, method_call.name(), Access::Read);
+                        self.walk_func_args(method_call.args());
+                    }
+                    Some(suffix) => self.walk_suffix(suffix),
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+        for suffix in suffixes {
+            self.walk_suffix(suffix);
+        }
+    }
+
+    fn walk_suffix(&mut self, suffix: &Suffix) {
+        match suffix {
+            Suffix::Call(Call::AnonymousCall(args)) => self.walk_func_args(args),
+            Suffix::Call(Call::MethodCall(method_call)) => self.walk_func_args(method_call.args()),
+            Suffix::Index(Index::Brackets { expression, .. }) => self.walk_expr(expression),
+            _ => {}
+        }
+    }
+
+    fn walk_func_args(&mut self, args: &FunctionArgs) {
+        match args {
+            FunctionArgs::Parentheses { arguments, .. } => self.walk_exprs(arguments),
+            FunctionArgs::TableConstructor(table) => self.walk_table(table),
+            _ => {}
+        }
+    }
+
+    fn walk_table(&mut self, table: &TableConstructor) {
+        for field in table.fields() {
+            match field {
+                Field::ExpressionKey { key, value, .. } => {
+                    self.walk_expr(key);
+                    self.walk_expr(value);
+                }
+                Field::NameKey { value, .. } | Field::NoKey(value) => self.walk_expr(value),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[test]
+fn test_scope_analysis() {
+    let src = r##"local a = 1
+local function f(x, ...)
+    local a = a + x
+    g = a
+    return function() return a, select("#", ...) end
+end
+function t:m(y)
+    for i = 1, #self do
+        local y = i
+    end
+    repeat local r = 1 until r
+end
+print(b.c, f)
+"##;
+    let ast = full_moon::parse(src).unwrap();
+    let analysis = ScopeAnalysis::from_ast(&ast);
+    let names: Vec<(&str, VariableKind)> =
+        analysis.variables.iter().map(|v| (v.name.as_str(), v.kind)).collect();
+    assert_eq!(
+        names,
+        vec![
+            ("a", VariableKind::Local),
+            ("f", VariableKind::LocalFunction),
+            ("x", VariableKind::Parameter),
+            ("...", VariableKind::Vararg),
+            ("a", VariableKind::Local),
+            ("self", VariableKind::SelfParameter),
+            ("y", VariableKind::Parameter),
+            ("i", VariableKind::ForVariable),
+            ("y", VariableKind::Local),
+            ("r", VariableKind::Local),
+        ]
+    );
+
+    // `local a = a + x` reads the outer `a` and hides it
+    let inner_a = &analysis.variables[4];
+    assert_eq!(inner_a.shadowed, Some(0));
+    assert_eq!(analysis.reads(0).count(), 1);
+    assert_eq!(analysis.reads(0).next().unwrap().pos.line, 3);
+    assert_eq!(analysis.variables[8].shadowed, Some(6));
+
+    // the inner `a` is read by `g = a` and as an upvalue by the closure
+    let reads: Vec<(usize, bool)> = analysis.reads(4).map(|r| (r.pos.line, r.upvalue)).collect();
+    assert_eq!(reads, vec![(4, false), (5, true)]);
+    assert_eq!(analysis.reads(3).count(), 1);
+    assert_eq!(analysis.reads(5).count(), 1);
+    assert_eq!(analysis.reads(9).count(), 1);
+    assert_eq!(analysis.reads(6).count(), 0);
+
+    let globals: Vec<(&str, Access)> =
+        analysis.globals().map(|r| (r.name.as_str(), r.access)).collect();
+    assert_eq!(
+        globals,
+        vec![
+            ("g", Access::Write),
+            ("select", Access::Read),
+            ("t", Access::Read),
+            ("print", Access::Read),
+            ("b", Access::Read),
+        ]
+    );
+    let f_ref = analysis.reference_at(13, 12).unwrap();
+    assert_eq!(analysis.declaration(f_ref).unwrap().kind, VariableKind::LocalFunction);
+    assert_eq!(
+        analysis.scopes[analysis.function_of(analysis.variables[7].scope)].kind,
+        ScopeKind::Function
+    );
+
+    let fields: Vec<Option<(&str, Access)>> = analysis
+        .globals()
+        .map(|r| r.field.as_ref().map(|field| (field.name.as_str(), field.access)))
+        .collect();
+    assert_eq!(
+        fields,
+        vec![None, None, Some(("m", Access::Write)), None, Some(("c", Access::Read))]
+    );
+}
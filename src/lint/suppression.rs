@@ -0,0 +1,164 @@
+use full_moon::tokenizer::{Token, TokenType};
+
+use crate::rules::{LintReport, Pos, ReportLevel};
+
+/// Reports of unused suppressions are listed under this name.
+pub const UNUSED_SUPPRESSION: &str = "unused_suppression";
+
+const DIRECTIVE_PREFIX: &str = "lualint:";
+
+/// A `-- lualint: ...` comment silencing one rule (or all of them when `rule`
+/// is `None`) on the lines `start..=end`.
+#[derive(Clone, Debug)]
+pub struct Suppression {
+    pub pos: Pos,
+    pub rule: Option<String>,
+    pub start: usize,
+    pub end: usize,
+    pub used: bool,
+}
+
+/// The suppression comments of a file:
+///
+/// ```lua
+/// -- lualint: disable-next-line max_column_width
+/// -- lualint: disable table_ctor_comma, func_separation
+/// -- lualint: enable table_ctor_comma
+/// -- lualint: disable-file eof_blank_line
+/// ```
+///
+/// Without rule names a directive applies to every rule. `disable` lasts until
+/// the matching `enable` or the end of the file, a bare `enable` ends them all.
+#[derive(Clone, Debug, Default)]
+pub struct Suppressions {
+    pub suppressions: Vec<Suppression>,
+}
+
+impl Suppressions {
+    pub fn from_tokens(tokens: &[Token]) -> Self {
+        let mut suppressions: Vec<Suppression> = vec![];
+        // indices of the `disable` suppressions still waiting for an `enable`
+        let mut open: Vec<usize> = vec![];
+        for token in tokens {
+            let comment = match token.token_type() {
+                TokenType::SingleLineComment { comment } => comment,
+                TokenType::MultiLineComment { comment, .. } => comment,
+                _ => continue,
+            };
+            let directive = match comment.trim().strip_prefix(DIRECTIVE_PREFIX) {
+                Some(directive) => directive.trim(),
+                None => continue,
+            };
+            let (command, rules) =
+                directive.split_once(char::is_whitespace).unwrap_or((directive, ""));
+            let rules: Vec<Option<String>> = match rules
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|rule| !rule.is_empty())
+                .map(|rule| Some(rule.to_string()))
+                .collect::<Vec<_>>()
+            {
+                rules if rules.is_empty() => vec![None],
+                rules => rules,
+            };
+            let pos = Pos::from(token.start_position());
+            let (start, end) = match command {
+                "disable-next-line" => {
+                    let next_line = token.end_position().line() + 1;
+                    (next_line, next_line)
+                }
+                "disable" => (pos.line, usize::MAX),
+                "disable-file" => (0, usize::MAX),
+                "enable" => {
+                    open.retain(|&i| {
+                        let suppression = &mut suppressions[i];
+                        let closed = rules[0].is_none() || rules.contains(&suppression.rule);
+                        if closed {
+                            suppression.end = pos.line;
+                        }
+                        !closed
+                    });
+                    continue;
+                }
+                // not a directive we know, leave the comment alone
+                _ => continue,
+            };
+            for rule in rules {
+                if command == "disable" {
+                    open.push(suppressions.len());
+                }
+                suppressions.push(Suppression { pos: pos.clone(), rule, start, end, used: false });
+            }
+        }
+        Self { suppressions }
+    }
+
+    /// Whether a report of `rule_name` on `line` is silenced, marking the
+    /// suppressions that silence it as used. Line 0 means the whole file and
+    /// is only silenced by `disable-file`.
+    pub fn suppresses(&mut self, rule_name: &str, line: usize) -> bool {
+        let mut suppressed = false;
+        for suppression in &mut self.suppressions {
+            let rule_matches = suppression.rule.as_deref().is_none_or(|rule| rule == rule_name);
+            let line_matches = if line == 0 {
+                suppression.start == 0
+            } else {
+                (suppression.start..=suppression.end).contains(&line)
+            };
+            if rule_matches && line_matches {
+                suppression.used = true;
+                suppressed = true;
+            }
+        }
+        suppressed
+    }
+
+    /// Reports the suppressions that did not silence anything.
+    pub fn unused_reports(&self) -> Vec<LintReport> {
+        self.suppressions
+            .iter()
+            .filter(|suppression| !suppression.used)
+            .map(|suppression| LintReport {
+                pos: suppression.pos.clone(),
+                level: ReportLevel::Warning,
+                msg: match &suppression.rule {
+                    Some(rule) => format!("Unused suppression of `{rule}`"),
+                    None => "Unused suppression of all rules".to_string(),
+                },
+                fix: None,
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_suppression_directives() {
+    let src = r#"-- lualint: disable-file eof_blank_line
+local a = 1 -- lualint: disable-next-line max_column_width
+local b = 2
+--[[ lualint: disable table_ctor_comma, func_separation ]]
+local c = 3
+-- lualint: enable table_ctor_comma
+local d = 4
+-- not a directive: lualint: disable
+"#;
+    let tokens = full_moon::tokenizer::tokens(src).unwrap();
+    let mut suppressions = Suppressions::from_tokens(&tokens);
+    assert_eq!(suppressions.suppressions.len(), 4);
+    assert!(suppressions.suppresses("eof_blank_line", 0));
+    assert!(!suppressions.suppresses("max_column_width", 0));
+    assert!(suppressions.suppresses("max_column_width", 3));
+    assert!(!suppressions.suppresses("max_column_width", 2));
+    assert!(suppressions.suppresses("table_ctor_comma", 5));
+    assert!(!suppressions.suppresses("table_ctor_comma", 7));
+    assert!(suppressions.suppresses("func_separation", 7));
+
+    let unused = suppressions.unused_reports();
+    assert!(unused.is_empty());
+
+    let tokens = full_moon::tokenizer::tokens("-- lualint: disable\nlocal a = 1\n").unwrap();
+    let suppressions = Suppressions::from_tokens(&tokens);
+    let unused = suppressions.unused_reports();
+    assert_eq!(unused.len(), 1);
+    assert_eq!(unused[0].msg, "Unused suppression of all rules");
+    assert_eq!(unused[0].pos.line, 1);
+}
@@ -23,8 +23,10 @@ fn main() {
     rules::init_all();
     if let Some(cmd) = &args.command {
         match cmd {
-            cli::Commands::Run { filename, rules, ignore, focus } => cli::handle_run_command(filename, rules, ignore.to_owned(), focus.to_owned()),
-            cli::Commands::Rules {} => cli::print_rules(),
+            cli::Commands::Run(args) => cli::handle_run_command(args),
+            cli::Commands::Baseline(args) => cli::handle_baseline_command(args),
+            cli::Commands::Rules(args) => cli::print_rules(args),
+            cli::Commands::Lsp(args) => cli::handle_lsp_command(args),
         }
     }
 }
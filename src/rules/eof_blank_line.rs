@@ -1,6 +1,12 @@
-use super::{LintReport, NodeWrapper, Pos, Registry, Rule, RuleContext, RuleInfo};
+use super::{Fix, LintReport, NodeWrapper, Pos, Registry, Rule, RuleContext, RuleInfo, TextEdit};
 
-decl_rule!(eof_blank_line, "Require a blank line at the end of the file", "20230224", "");
+decl_rule!(
+    eof_blank_line,
+    EofBlankLine,
+    "Require a blank line at the end of the file",
+    "20230224",
+    ""
+);
 
 pub struct EofBlankLine {
     pub reports: Vec<LintReport>,
@@ -33,6 +39,7 @@ impl EofBlankLine {
                 pos: Pos::new(0, 0),
                 level: super::ReportLevel::Warning,
                 msg: "File is expected to end with a blank line, but does not".to_string(),
+                fix: Some(Fix::safe(vec![TextEdit::insert(source.len(), "\n")])),
             });
         }
 
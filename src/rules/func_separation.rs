@@ -3,13 +3,22 @@ use full_moon::{
     tokenizer::{Token, TokenType},
 };
 
-use super::{LintReport, NodeKey, NodeWrapper, Registry, Rule, RuleContext, RuleInfo};
+use super::{
+    Fix, LintReport, NodeKey, NodeWrapper, OptionValue, Registry, Rule, RuleContext, RuleInfo,
+    RuleOption, TextEdit,
+};
 
 decl_rule!(
     func_separation,
+    FuncSeparation,
     "Require a blank line between function declarations",
     "20230224",
-    "min_line: 2"
+    "min_line: 2",
+    [RuleOption::new(
+        "min_line",
+        OptionValue::Integer(2),
+        "Minimum number of line breaks between two function declarations"
+    )]
 );
 pub struct FuncSeparation {
     pub min_empty_line: usize,
@@ -25,7 +34,7 @@ impl RuleContext for FuncSeparation {
 
 impl Rule for FuncSeparation {
     fn apply(rules: &mut Registry, config: &serde_json::Value) -> Self {
-        let min_linebreak = config.get("min_line").and_then(|v| v.as_u64()).unwrap_or(2) as usize;
+        let min_linebreak = RULE_INFO.option(config, "min_line").as_u64().unwrap() as usize;
 
         Self::apply(rules, min_linebreak)
     }
@@ -57,12 +66,19 @@ impl FuncSeparation {
                                 + Self::check(y.function_token().leading_trivia());
                 let empty_line = n_linebreak - 1;
                 if empty_line < ctx.min_empty_line {
+                    // insert the missing lines after the trailing trivia of `end`, so that
+                    // comments on the same line stay where they are
+                    let end_token = x.body().end_token();
+                    let insert_at = end_token.end_position().bytes()
+                        + end_token.trailing_trivia().map(|t| t.to_string().len()).sum::<usize>();
+                    let missing = "\n".repeat(ctx.min_empty_line - empty_line);
                     ctx.reports.push(LintReport { pos: x.body().end_token().end_position().into(), level: super::ReportLevel::Warning,
                         msg: format!(
                             "Function declaration '{}' should be separated from the previous function declaration by at least {} empty lines",
                             y.name(),
                             ctx.min_empty_line
-                        ) });
+                        ),
+                        fix: Some(Fix::safe(vec![TextEdit::insert(insert_at, &missing)])) });
                 }
             }
             prev_stmt = Some(stmt.clone());
@@ -0,0 +1,222 @@
+use std::collections::{BTreeSet, HashMap};
+
+use full_moon::{
+    ast::{Block, Field, FunctionArgs, LastStmt, Stmt, TableConstructor},
+    node::Node,
+    tokenizer::{Symbol, Token, TokenType},
+    visitors::{Visit, Visitor},
+};
+
+use super::{
+    Fix, LintReport, NodeKey, NodeWrapper, OptionValue, Pos, Registry, ReportLevel, Rule,
+    RuleContext, RuleInfo, RuleOption, TextEdit,
+};
+
+decl_rule!(
+    indent_with_spaces,
+    IndentWithSpaces,
+    "Indent each line with spaces by the depth of its block",
+    "20231017",
+    "width: 4",
+    [RuleOption::new("width", OptionValue::Integer(4), "Number of spaces per indentation level")]
+);
+pub struct IndentWithSpaces {
+    pub reports: Vec<LintReport>,
+
+    width: usize,
+    /// the source as the tokens were read from, for the indentation of lines
+    source: String,
+    /// whether the file's block was checked, nested blocks are checked with it
+    checked: bool,
+}
+
+impl Rule for IndentWithSpaces {
+    fn apply(rules: &mut Registry, config: &serde_json::Value) -> Self {
+        let rule_name = "indent_with_spaces";
+        rules.preprocess(rule_name, Self::preprocess);
+        rules.listen_enter(rule_name, NodeKey::Block, Self::enter_block);
+
+        let width = RULE_INFO.option(config, "width").as_u64().unwrap() as usize;
+
+        Self { reports: vec![], width, source: String::new(), checked: false }
+    }
+
+    fn context(&self) -> &dyn RuleContext {
+        self
+    }
+}
+
+impl RuleContext for IndentWithSpaces {
+    fn get_reports(&self) -> &Vec<LintReport> {
+        &self.reports
+    }
+}
+
+impl IndentWithSpaces {
+    pub fn preprocess(rctx: &mut dyn RuleContext, node: NodeWrapper) -> NodeWrapper {
+        let ctx: &mut IndentWithSpaces = rctx.downcast_mut().unwrap();
+        let source = rule_cast!(node, NodeWrapper::Source);
+        ctx.source = source.clone();
+        NodeWrapper::Source(source)
+    }
+
+    pub fn enter_block(rctx: &mut dyn RuleContext, node: NodeWrapper) -> NodeWrapper {
+        let block = rule_cast!(node, NodeWrapper::Block);
+        let ctx: &mut IndentWithSpaces = rctx.downcast_mut().unwrap();
+        if ctx.checked {
+            return NodeWrapper::Block(block);
+        }
+        ctx.checked = true;
+
+        let mut depths = DepthVisitor::default();
+        block.visit(&mut depths);
+
+        let mut line_start = 0;
+        for (i, line) in ctx.source.split('\n').enumerate() {
+            let start = line_start;
+            line_start += line.len() + 1;
+            let indent_len = line.len() - line.trim_start_matches([' ', '\t']).len();
+            let token_start = start + indent_len;
+            // blank lines, comments and the inside of multi-line strings
+            if !depths.token_starts.contains(&token_start) {
+                continue;
+            }
+            let indent = &line[..indent_len];
+            let (expected, msg) = match depths.depths.get(&token_start) {
+                Some(&depth) => {
+                    let expected = " ".repeat(depth * ctx.width);
+                    let msg = format!(
+                        "Line should be indented with {} spaces, found {}",
+                        expected.len(),
+                        describe(indent)
+                    );
+                    (expected, msg)
+                }
+                // a continued expression, which may be aligned freely
+                None => {
+                    let expected = indent.replace('\t', &" ".repeat(ctx.width));
+                    (expected, "Line is indented with tabs".to_string())
+                }
+            };
+            if indent == expected {
+                continue;
+            }
+            ctx.reports.push(LintReport {
+                pos: Pos::new(i + 1, 1),
+                level: ReportLevel::Warning,
+                msg,
+                fix: Some(Fix::safe(vec![TextEdit::replace(start, token_start, &expected)])),
+            });
+        }
+
+        NodeWrapper::Block(block)
+    }
+}
+
+fn describe(indent: &str) -> String {
+    match (indent.contains(' '), indent.contains('\t')) {
+        (_, false) => format!("{}", indent.len()),
+        (false, true) => "tabs".to_string(),
+        (true, true) => "tabs and spaces".to_string(),
+    }
+}
+
+/// Finds the depth of the lines starting with a statement, a table field, an
+/// argument of a call whose arguments start on a new line, or the keyword or
+/// bracket closing one of them.
+#[derive(Default)]
+struct DepthVisitor {
+    /// the number of blocks, tables and continued argument lists around the
+    /// visited node, the file's block included
+    depth: usize,
+    /// whether the visited calls have their arguments on new lines
+    continued_args: Vec<bool>,
+    /// the expected depth of the tokens by their byte offset
+    depths: HashMap<usize, usize>,
+    token_starts: BTreeSet<usize>,
+}
+
+impl DepthVisitor {
+    fn mark(&mut self, node: &impl Node, depth: usize) {
+        if let Some(start) = node.start_position() {
+            self.depths.insert(start.bytes(), depth);
+        }
+    }
+}
+
+impl Visitor for DepthVisitor {
+    fn visit_block(&mut self, _block: &Block) {
+        self.depth += 1;
+    }
+
+    fn visit_block_end(&mut self, _block: &Block) {
+        self.depth -= 1;
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.mark(stmt, self.depth - 1);
+    }
+
+    fn visit_last_stmt(&mut self, last_stmt: &LastStmt) {
+        self.mark(last_stmt, self.depth - 1);
+    }
+
+    fn visit_table_constructor(&mut self, table: &TableConstructor) {
+        self.mark(table.braces().tokens().1, self.depth - 1);
+        self.depth += 1;
+    }
+
+    fn visit_table_constructor_end(&mut self, _table: &TableConstructor) {
+        self.depth -= 1;
+    }
+
+    fn visit_field(&mut self, field: &Field) {
+        self.mark(field, self.depth - 1);
+    }
+
+    fn visit_function_args(&mut self, args: &FunctionArgs) {
+        let continued = match args {
+            FunctionArgs::Parentheses { parentheses, arguments } => {
+                let (open, close) = parentheses.tokens();
+                let first_line = arguments.iter().next().and_then(|a| a.start_position());
+                match first_line {
+                    Some(first) if first.line() > open.start_position().unwrap().line() => {
+                        self.mark(close, self.depth - 1);
+                        for argument in arguments {
+                            self.mark(argument, self.depth);
+                        }
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        };
+        self.depth += usize::from(continued);
+        self.continued_args.push(continued);
+    }
+
+    fn visit_function_args_end(&mut self, _args: &FunctionArgs) {
+        let continued = self.continued_args.pop().unwrap_or_default();
+        self.depth -= usize::from(continued);
+    }
+
+    fn visit_token(&mut self, token: &Token) {
+        match token.token_type() {
+            TokenType::Whitespace { .. }
+            | TokenType::SingleLineComment { .. }
+            | TokenType::MultiLineComment { .. }
+            | TokenType::Eof => {}
+            TokenType::Symbol { symbol } => {
+                self.token_starts.insert(token.start_position().bytes());
+                // the blocks they close were left already
+                if matches!(symbol, Symbol::End | Symbol::Else | Symbol::ElseIf | Symbol::Until) {
+                    self.depths.insert(token.start_position().bytes(), self.depth - 1);
+                }
+            }
+            _ => {
+                self.token_starts.insert(token.start_position().bytes());
+            }
+        }
+    }
+}
@@ -1,6 +1,19 @@
-use super::{LintReport, NodeWrapper, Registry, Rule, RuleContext, RuleInfo};
+use super::{
+    LintReport, NodeWrapper, OptionValue, Registry, Rule, RuleContext, RuleInfo, RuleOption,
+};
 
-decl_rule!(max_column_width, "Maximum column width", "20230224", "max_col: 80");
+decl_rule!(
+    max_column_width,
+    MaxColumnWidth,
+    "Maximum column width",
+    "20230224",
+    "max_col: 80",
+    [RuleOption::new(
+        "max_col",
+        OptionValue::Integer(80),
+        "Maximum number of characters in a line"
+    )]
+);
 pub struct MaxColumnWidth {
     pub reports: Vec<LintReport>,
 
@@ -14,7 +27,7 @@ impl Rule for MaxColumnWidth {
         let rule_name = "max_column_width";
         rules.listen_token(rule_name, Self::on_token);
 
-        let max_column_width = config["max_col"].as_u64().unwrap_or(80) as usize;
+        let max_column_width = RULE_INFO.option(config, "max_col").as_u64().unwrap() as usize;
 
         Self { reports: vec![], max_column_width, _last_check_line: 0 }
     }
@@ -56,6 +69,7 @@ impl MaxColumnWidth {
                     "Line is expected to be at most {} characters, but is more than {} characters",
                     ctx.max_column_width, real_len
                 ),
+                fix: None,
             });
             ctx._last_check_line = token.end_position().line();
         }
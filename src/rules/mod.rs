@@ -3,11 +3,16 @@ use linked_hash_map::LinkedHashMap;
 use std::sync::Mutex;
 
 use downcast_rs::{impl_downcast, Downcast};
+#[cfg(feature = "lua52")]
+use full_moon::ast::lua52::{Goto, Label};
 use full_moon::{
-    ast::{lua52::Goto, *},
+    ast::*,
+    node::Node,
     tokenizer::{Position, Token, TokenReference},
 };
 
+use crate::lint::ScopeAnalysis;
+
 macro_rules! rule_cast {
     ($rule: expr, $rule_type: path) => {
         match $rule {
@@ -18,7 +23,17 @@ macro_rules! rule_cast {
 }
 
 macro_rules! decl_rule {
-    ($rule_name:ident, $description:expr, $version:expr, $config_example:expr) => {
+    ($rule_name:ident, $rule_type:ty, $description:expr, $version:expr, $config_example:expr) => {
+        decl_rule!($rule_name, $rule_type, $description, $version, $config_example, []);
+    };
+    (
+        $rule_name:ident,
+        $rule_type:ty,
+        $description:expr,
+        $version:expr,
+        $config_example:expr,
+        [$($option:expr),* $(,)?]
+    ) => {
         pub const RULE_NAME: &'static str = stringify!($rule_name);
 
         pub const RULE_INFO: RuleInfo = RuleInfo {
@@ -26,45 +41,104 @@ macro_rules! decl_rule {
             description: $description,
             version: $version,
             config_example: $config_example,
+            options: &[$($option),*],
+            create: super::create_rule::<$rule_type>,
         };
-
-        pub fn init() {
-            super::ALL_RULES.lock().unwrap().insert(RULE_NAME, RULE_INFO);
-        }
     };
 }
 macro_rules! decl_rules {
     ($($mod_name:ident),+) => {
         $(pub mod $mod_name;)+
-        pub fn init_all() {
-            $( $mod_name::init(); )+
-        }
+        /// The rules that come with lualint, in `ALL_RULES` from the start.
+        pub const BUILTIN_RULES: &[RuleInfo] = &[$($mod_name::RULE_INFO),+];
     };
 }
 
 decl_rules!(
     eof_blank_line,
     func_separation,
+    indent_with_spaces,
     max_column_width,
+    no_trailing_semicolon,
     no_trailing_space,
     one_line_before_else,
-    table_ctor_comma
+    operator_spacing,
+    shadowing,
+    table_ctor_comma,
+    undefined_global,
+    unused_variable
 );
 
+pub mod options;
+pub mod std_globals;
+pub mod syntax_error;
+
+pub type OptionValue = options::OptionValue;
+pub type RuleOption = options::RuleOption;
+
+/// Creates a rule from its options: registers its callbacks in the registry
+/// and returns its context, which the caller binds under the rule's name.
+pub type RuleFactory = fn(rules: &mut Registry, config: &serde_json::Value) -> Box<dyn RuleContext>;
+
+#[derive(Clone, Copy)]
 pub struct RuleInfo {
     pub name: &'static str,
     pub description: &'static str,
     pub version: &'static str,
     pub config_example: &'static str,
+    /// the options the rule reads from its config table, besides `severity`
+    pub options: &'static [RuleOption],
+    pub create: RuleFactory,
+}
+
+impl RuleInfo {
+    /// Describes the rule `T`, for rules defined outside this crate.
+    pub const fn of<T: Rule + RuleContext>(
+        name: &'static str,
+        description: &'static str,
+        version: &'static str,
+        config_example: &'static str,
+    ) -> Self {
+        Self { name, description, version, config_example, options: &[], create: create_rule::<T> }
+    }
+
+    pub const fn with_options(mut self, options: &'static [RuleOption]) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+pub fn create_rule<T: Rule + RuleContext>(
+    rules: &mut Registry,
+    config: &serde_json::Value,
+) -> Box<dyn RuleContext> {
+    Box::new(T::apply(rules, config))
 }
 
 lazy_static::lazy_static! {
     pub static  ref ALL_RULES: std::sync::Mutex<LinkedHashMap<&'static str, RuleInfo>> = {
-        let map = LinkedHashMap::new();
+        let map = BUILTIN_RULES.iter().map(|info| (info.name, *info)).collect();
         Mutex::new(map)
     };
 }
 
+/// Makes a rule known to `LinterBuilder::with_rule_by_name` and `lualint rules`,
+/// replacing any rule of the same name. The rule must register its callbacks
+/// and report under `info.name`.
+pub fn register_rule(info: RuleInfo) {
+    ALL_RULES.lock().unwrap().insert(info.name, info);
+}
+
+pub fn find_rule(rule_name: &str) -> Option<RuleInfo> {
+    ALL_RULES.lock().unwrap().get(rule_name).copied()
+}
+
+/// Kept for callers from before `ALL_RULES` filled itself, it only makes sure
+/// the built-in rules are there.
+pub fn init_all() {
+    lazy_static::initialize(&ALL_RULES);
+}
+
 #[derive(Clone, Debug)]
 pub struct Pos {
     pub file: String,
@@ -109,19 +183,29 @@ pub enum NodeKey {
     If,
     LocalAssign,
     LocalFunc,
-    Numericfor,
     Repeat,
     While,
     FuncArgsBlock,
     Field,
     TokenRef,
     FuncName,
+    Label,
+}
+
+impl NodeKey {
+    /// The old name of `NumericFor`, kept for the callers of `listen_enter`/`listen_leave`.
+    #[deprecated(note = "renamed to `NodeKey::NumericFor`")]
+    #[allow(non_upper_case_globals)]
+    pub const Numericfor: NodeKey = NodeKey::NumericFor;
 }
 
 pub enum NodeWrapper {
     Source(String),
     Token(Token),
+    #[cfg(feature = "lua52")]
     Goto(Goto),
+    #[cfg(feature = "lua52")]
+    Label(Label),
     TokenRef(TokenReference),
     Block(Block),
     LastStmt(LastStmt),
@@ -133,6 +217,7 @@ pub enum NodeWrapper {
     FunctionArgs(FunctionArgs),
     Assignment(Assignment),
     TableConstructor(TableConstructor),
+    Field(Field),
     Do(Do),
     FunctionName(FunctionName),
     FunctionDeclaration(FunctionDeclaration),
@@ -149,6 +234,8 @@ pub enum WalkTy {
     Leave,
 }
 type RuleCallback = fn(rctx: &mut dyn RuleContext, rule: NodeWrapper) -> NodeWrapper;
+/// Receives the scopes of the file before its AST is walked.
+type ScopeCallback = fn(rctx: &mut dyn RuleContext, scopes: &ScopeAnalysis);
 type CallbackIndex = usize;
 #[derive(Default)]
 pub struct Registry {
@@ -159,6 +246,9 @@ pub struct Registry {
     preprocessors: Vec<CallbackIndex>,
     pub rule_ctx: LinkedHashMap<String, Box<dyn RuleContext>>,
     callback_id_to_name: LinkedHashMap<CallbackIndex, String>,
+    scope_listeners: Vec<(String, ScopeCallback)>,
+    // what `begin_file` creates the contexts of the rules from
+    rule_factories: LinkedHashMap<String, (RuleFactory, serde_json::Value)>,
 }
 pub trait RuleContext: Downcast {
     fn get_reports(&self) -> &Vec<LintReport>;
@@ -171,18 +261,110 @@ pub trait Rule {
 
 impl_downcast!(RuleContext);
 
-#[derive(Debug, Clone)]
+/// Ordered from the least to the most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ReportLevel {
     Info,
     Warning,
     Error,
 }
 
+impl std::str::FromStr for ReportLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "info" => Ok(ReportLevel::Info),
+            "warning" => Ok(ReportLevel::Warning),
+            "error" => Ok(ReportLevel::Error),
+            _ => Err(format!("unknown level `{s}`, expected one of info, warning or error")),
+        }
+    }
+}
+
+impl std::fmt::Display for ReportLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ReportLevel::Info => write!(f, "info"),
+            ReportLevel::Warning => write!(f, "warning"),
+            ReportLevel::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Replaces the bytes in `start..end` of the linted source with `replacement`.
+/// An empty range is an insertion, an empty replacement is a deletion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    pub fn insert(at: usize, text: &str) -> Self {
+        Self { start: at, end: at, replacement: text.to_string() }
+    }
+
+    pub fn delete(start: usize, end: usize) -> Self {
+        Self { start, end, replacement: String::new() }
+    }
+
+    pub fn replace(start: usize, end: usize, text: &str) -> Self {
+        Self { start, end, replacement: text.to_string() }
+    }
+}
+
+/// A suggested fix for a report. `safe` fixes never change the behavior of the
+/// program and can be applied without review.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fix {
+    pub edits: Vec<TextEdit>,
+    pub safe: bool,
+}
+
+impl Fix {
+    pub fn safe(edits: Vec<TextEdit>) -> Self {
+        Self { edits, safe: true }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LintReport {
     pub pos: Pos,
     pub level: ReportLevel,
     pub msg: String,
+    pub fix: Option<Fix>,
+}
+
+/// Applies `edits` to `src`. Edits overlapping an earlier one are skipped.
+pub fn apply_edits(src: &str, edits: &[TextEdit]) -> String {
+    let mut edits: Vec<&TextEdit> = edits.iter().collect();
+    edits.sort_by_key(|edit| (edit.start, edit.end));
+
+    let mut out = String::with_capacity(src.len());
+    let mut cursor = 0;
+    for edit in edits {
+        if edit.start < cursor || edit.end > src.len() {
+            continue;
+        }
+        out.push_str(&src[cursor..edit.start]);
+        out.push_str(&edit.replacement);
+        cursor = edit.end;
+    }
+    out.push_str(&src[cursor..]);
+    out
+}
+
+/// The first token of `node` in the source. `Node::tokens` yields brackets
+/// before what they contain, so the order of the tokens cannot be relied on.
+pub fn first_token(node: &impl Node) -> Option<&TokenReference> {
+    node.tokens().min_by_key(|token| token.token().start_position().bytes())
+}
+
+/// The last token of `node` in the source, e.g. the `)` of `f(a)`.
+pub fn last_token(node: &impl Node) -> Option<&TokenReference> {
+    node.tokens().max_by_key(|token| token.token().end_position().bytes())
 }
 
 impl Registry {
@@ -201,6 +383,30 @@ impl Registry {
         self.rule_ctx.insert(rule_name.to_string(), ctx);
     }
 
+    /// Creates a rule with `create`, which registers its callbacks, and binds
+    /// its context. `begin_file` creates the context again from `config`.
+    pub fn add_rule(&mut self, rule_name: &str, create: RuleFactory, config: &serde_json::Value) {
+        let ctx = create(self, config);
+        self.bind_ctx(rule_name, ctx);
+        self.rule_factories.insert(rule_name.to_string(), (create, config.clone()));
+    }
+
+    /// Starts a new file: the rules added with `add_rule` get a fresh context,
+    /// dropping the reports and state left by the last file. Contexts that no
+    /// callback uses, like the driver's `syntax_error`, are dropped.
+    pub fn begin_file(&mut self) {
+        let contexts = std::mem::take(&mut self.rule_ctx);
+        for (rule_name, ctx) in contexts {
+            let ctx = match self.rule_factories.get(&rule_name) {
+                // the callbacks are registered already, only the context is kept
+                Some((create, config)) => create(&mut Registry::default(), config),
+                None if self.callback_id_to_name.values().any(|name| *name == rule_name) => ctx,
+                None => continue,
+            };
+            self.rule_ctx.insert(rule_name, ctx);
+        }
+    }
+
     // pub fn register(&mut self, rule_name: &str, node_type: NodeKey, callback: Box<RuleCallback>) {
     //     let callback_index = self.callbacks.len();
     //     self.callbacks.push(callback);
@@ -253,6 +459,23 @@ impl Registry {
         source
     }
 
+    /// Asks for the scope analysis of every file, which is only run when a
+    /// rule listens to it.
+    pub fn listen_scopes(&mut self, rule_name: &str, callback: ScopeCallback) {
+        self.scope_listeners.push((rule_name.to_string(), callback));
+    }
+
+    pub fn has_scope_listeners(&self) -> bool {
+        !self.scope_listeners.is_empty()
+    }
+
+    pub fn notify_scopes(&mut self, scopes: &ScopeAnalysis) {
+        for (rule_name, callback) in &self.scope_listeners {
+            let ctx: &mut dyn RuleContext = self.rule_ctx.get_mut(rule_name).unwrap().as_mut();
+            callback(ctx, scopes);
+        }
+    }
+
     pub fn listen_token(&mut self, rule_name: &str, callback: RuleCallback) {
         let callback_index = self.callbacks.len();
         self.callbacks.push(callback);
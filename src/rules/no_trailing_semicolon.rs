@@ -1,15 +1,41 @@
-use full_moon::{
-    ast::{punctuated::Pair, Stmt},
-    node::Node,
-    tokenizer::{Position, Token, TokenType},
-};
+use full_moon::tokenizer::{Symbol, TokenReference, TokenType};
 
-use crate::lint::trim_lua_comments;
+use super::{
+    first_token, Fix, LintReport, NodeKey, NodeWrapper, OptionValue, Pos, Registry, ReportLevel,
+    Rule, RuleContext, RuleInfo, RuleOption, TextEdit,
+};
 
-use super::{LintReport, NodeKey, NodeWrapper, Pos, Registry, RuleContext, WalkTy};
+decl_rule!(
+    no_trailing_semicolon,
+    NoTrailingSemicolon,
+    "No semicolons at the end of statements",
+    "20231017",
+    "allow_separator: false",
+    [RuleOption::new(
+        "allow_separator",
+        OptionValue::Boolean(false),
+        "Allow `;` between statements on the same line, e.g. `a = 1; b = 2`"
+    )]
+);
 pub struct NoTrailingSemicolon {
-    
     pub reports: Vec<LintReport>,
+
+    allow_separator: bool,
+}
+
+impl Rule for NoTrailingSemicolon {
+    fn apply(rules: &mut Registry, config: &serde_json::Value) -> Self {
+        let rule_name = "no_trailing_semicolon";
+        rules.listen_enter(rule_name, NodeKey::Block, Self::enter_block);
+
+        let allow_separator = RULE_INFO.option(config, "allow_separator").as_bool().unwrap();
+
+        Self { reports: vec![], allow_separator }
+    }
+
+    fn context(&self) -> &dyn RuleContext {
+        self
+    }
 }
 
 impl RuleContext for NoTrailingSemicolon {
@@ -19,30 +45,61 @@ impl RuleContext for NoTrailingSemicolon {
 }
 
 impl NoTrailingSemicolon {
-    pub fn apply(rules: &mut Registry) -> Self {
-    let rule_name = "no_trailing_semicolon";
-        rules.preprocess(rule_name, Box::new(Self::preprocess));
+    pub fn enter_block(rctx: &mut dyn RuleContext, node: NodeWrapper) -> NodeWrapper {
+        let block = rule_cast!(node, NodeWrapper::Block);
+        let ctx: &mut NoTrailingSemicolon = rctx.downcast_mut().unwrap();
 
-        Self {  reports: vec![] }
+        let mut next_starts =
+            block.stmts().skip(1).map(|stmt| first_token(stmt)).collect::<Vec<_>>();
+        next_starts.push(block.last_stmt().and_then(first_token));
+
+        for ((_, semicolon), next_start) in block.stmts_with_semicolon().zip(next_starts) {
+            match semicolon {
+                Some(semicolon) if ctx.is_trailing(semicolon, next_start) => {
+                    ctx.report(semicolon, next_start)
+                }
+                _ => {}
+            }
+        }
+        if let Some((_, Some(semicolon))) = block.last_stmt_with_semicolon() {
+            ctx.report(semicolon, None);
+        }
+
+        NodeWrapper::Block(block)
     }
 
-    pub fn preprocess(rctx: &mut dyn RuleContext, node: NodeWrapper) -> NodeWrapper {
-        let ctx: &mut NoTrailingSemicolon = rctx.downcast_mut().unwrap();
-        let source = rule_cast!(node, NodeWrapper::Source);
-        let source = trim_lua_comments(source.as_str());
-        let mut lineno = 0;
-        source.lines().for_each(|line| {
-            lineno += 1;
-            if line.ends_with(";") {
-                let col = line.len() - line.trim_end().len() + 1;
-                ctx.reports.push(LintReport {
-                    pos: Pos::new(lineno, col),
-                    level: super::ReportLevel::Warning,
-                    msg: format!("Line ends with trailing semicolon",),
-                });
+    /// Whether `semicolon` may be removed: a `;` before a statement starting
+    /// with `(` keeps it from being read as the arguments of a call.
+    fn is_trailing(&self, semicolon: &TokenReference, next_start: Option<&TokenReference>) -> bool {
+        match next_start {
+            Some(next) if is_open_paren(next) => false,
+            Some(next) if self.allow_separator => !same_line(semicolon, next),
+            _ => true,
+        }
+    }
+
+    fn report(&mut self, semicolon: &TokenReference, next_start: Option<&TokenReference>) {
+        let msg = match next_start {
+            Some(next) if same_line(semicolon, next) => {
+                "Semicolon separates statements on one line"
             }
+            _ => "Statement ends with a trailing semicolon",
+        };
+        let start = semicolon.token().start_position();
+        let end = semicolon.token().end_position();
+        self.reports.push(LintReport {
+            pos: Pos::new(start.line(), start.character()),
+            level: ReportLevel::Warning,
+            msg: msg.to_string(),
+            fix: Some(Fix::safe(vec![TextEdit::delete(start.bytes(), end.bytes())])),
         });
-
-        NodeWrapper::Source(source)
     }
 }
+
+fn same_line(semicolon: &TokenReference, next: &TokenReference) -> bool {
+    semicolon.token().start_position().line() == next.token().start_position().line()
+}
+
+fn is_open_paren(token: &TokenReference) -> bool {
+    matches!(token.token_type(), TokenType::Symbol { symbol: Symbol::LeftParen })
+}
@@ -1,8 +1,8 @@
-use crate::lint::trim_lua_comments;
+use full_moon::tokenizer::TokenType;
 
-use super::{LintReport, NodeWrapper, Pos, Registry, RuleContext, RuleInfo};
+use super::{Fix, LintReport, NodeWrapper, Pos, Registry, Rule, RuleContext, RuleInfo, TextEdit};
 
-decl_rule!(no_trailing_space, "Disallow trailing whitespace", "20230224", "");
+decl_rule!(no_trailing_space, NoTrailingWhitespace, "Disallow trailing whitespace", "20230224", "");
 
 pub struct NoTrailingWhitespace {
     pub reports: Vec<LintReport>,
@@ -14,30 +14,46 @@ impl RuleContext for NoTrailingWhitespace {
     }
 }
 
-impl NoTrailingWhitespace {
-    pub fn apply(rules: &mut Registry) -> Self {
-        let rule_name = "no_trailing_space";
-        rules.preprocess(rule_name, Self::preprocess);
-
+impl Rule for NoTrailingWhitespace {
+    fn apply(rules: &mut Registry, _config: &serde_json::Value) -> Self {
+        rules.preprocess(RULE_NAME, Self::preprocess);
         Self { reports: vec![] }
     }
 
+    fn context(&self) -> &dyn RuleContext {
+        self
+    }
+}
+
+impl NoTrailingWhitespace {
     pub fn preprocess(rctx: &mut dyn RuleContext, node: NodeWrapper) -> NodeWrapper {
         let ctx: &mut NoTrailingWhitespace = rctx.downcast_mut().unwrap();
         let source = rule_cast!(node, NodeWrapper::Source);
-        let source = trim_lua_comments(source.as_str());
-        let mut lineno = 0;
-        source.lines().for_each(|line| {
-            lineno += 1;
-            if line.ends_with(' ') || line.ends_with('\t') {
-                let col = line.len() - line.trim_end().len() + 1;
+        // only whitespace tokens are checked: strings and comments may end with spaces on purpose
+        let tokens = match full_moon::tokenizer::tokens(source.as_str()) {
+            Ok(tokens) => tokens,
+            Err(_) => return NodeWrapper::Source(source),
+        };
+        for (i, token) in tokens.iter().enumerate() {
+            if let TokenType::Whitespace { characters } = token.token_type() {
+                let at_line_end = characters.ends_with('\n')
+                    || matches!(tokens.get(i + 1).map(|t| t.token_type()), Some(TokenType::Eof));
+                let trailing = characters.trim_end_matches(&['\r', '\n'][..]);
+                if !at_line_end || trailing.is_empty() {
+                    continue;
+                }
+                let start = token.start_position();
                 ctx.reports.push(LintReport {
-                    pos: Pos::new(lineno, col),
+                    pos: Pos::new(start.line(), start.character()),
                     level: super::ReportLevel::Warning,
                     msg: "Line ends with trailing whitespace".to_string(),
+                    fix: Some(Fix::safe(vec![TextEdit::delete(
+                        start.bytes(),
+                        start.bytes() + trailing.len(),
+                    )])),
                 });
             }
-        });
+        }
 
         NodeWrapper::Source(source)
     }
@@ -2,7 +2,13 @@ use full_moon::node::Node;
 
 use super::{LintReport, NodeKey, NodeWrapper, Registry, Rule, RuleContext, RuleInfo};
 
-decl_rule!(one_line_before_else, "Require a blank line before else", "20230224", "");
+decl_rule!(
+    one_line_before_else,
+    OneLineBeforeElse,
+    "Require a blank line before else",
+    "20230224",
+    ""
+);
 pub struct OneLineBeforeElse {
     pub reports: Vec<LintReport>,
 }
@@ -58,6 +64,7 @@ impl OneLineBeforeElse {
                         pos: else_if_token.start_position().unwrap().into(),
                         level: super::ReportLevel::Warning,
                         msg: "There should be a line before else if".to_string(),
+                        fix: None,
                     });
                 }
 
@@ -83,6 +90,7 @@ impl OneLineBeforeElse {
                     pos: else_token.start_position().unwrap().into(),
                     level: super::ReportLevel::Warning,
                     msg: "There should be a line before else".to_string(),
+                    fix: None,
                 });
             }
         }
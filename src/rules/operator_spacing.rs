@@ -0,0 +1,207 @@
+use full_moon::{
+    ast::{Assignment, BinOp, Expression, LocalAssignment, UnOp},
+    tokenizer::{TokenReference, TokenType},
+};
+
+use super::{
+    first_token, last_token, Fix, LintReport, NodeKey, NodeWrapper, OptionValue, Pos, Registry,
+    ReportLevel, Rule, RuleContext, RuleInfo, RuleOption, TextEdit,
+};
+
+decl_rule!(
+    operator_spacing,
+    OperatorSpacing,
+    "Keep one space on each side of binary operators and `=`",
+    "20231017",
+    "space_after_unary: false, check_concat: true, exempt_caret: false",
+    [
+        RuleOption::new(
+            "space_after_unary",
+            OptionValue::Boolean(false),
+            "Require one space after the unary `-`, `#` and `~` instead of none"
+        ),
+        RuleOption::new("check_concat", OptionValue::Boolean(true), "Check the spaces around `..`"),
+        RuleOption::new(
+            "exempt_caret",
+            OptionValue::Boolean(false),
+            "Do not check the spaces around `^`, e.g. to allow `x^2`"
+        )
+    ]
+);
+pub struct OperatorSpacing {
+    pub reports: Vec<LintReport>,
+
+    space_after_unary: bool,
+    check_concat: bool,
+    exempt_caret: bool,
+}
+
+impl Rule for OperatorSpacing {
+    fn apply(rules: &mut Registry, config: &serde_json::Value) -> Self {
+        let rule_name = "operator_spacing";
+        rules.listen_enter(rule_name, NodeKey::ExprBlock, Self::enter_expr);
+        rules.listen_enter(rule_name, NodeKey::Assignment, Self::enter_assignment);
+        rules.listen_enter(rule_name, NodeKey::LocalAssign, Self::enter_local_assign);
+
+        let option = |name: &str| RULE_INFO.option(config, name).as_bool().unwrap();
+        Self {
+            reports: vec![],
+            space_after_unary: option("space_after_unary"),
+            check_concat: option("check_concat"),
+            exempt_caret: option("exempt_caret"),
+        }
+    }
+
+    fn context(&self) -> &dyn RuleContext {
+        self
+    }
+}
+
+impl RuleContext for OperatorSpacing {
+    fn get_reports(&self) -> &Vec<LintReport> {
+        &self.reports
+    }
+}
+
+/// The whitespace between two tokens on the same line, `None` if a line
+/// break or a comment separates them.
+fn gap(before: &TokenReference, after: &TokenReference) -> Option<String> {
+    let mut gap = String::new();
+    for token in before.trailing_trivia().chain(after.leading_trivia()) {
+        match token.token_type() {
+            TokenType::Whitespace { characters } if !characters.contains('\n') => {
+                gap.push_str(characters)
+            }
+            _ => return None,
+        }
+    }
+    Some(gap)
+}
+
+impl OperatorSpacing {
+    /// Reports `operator` if the gaps around it are not `expected`, with the
+    /// edits that fix them. A side that spans lines or holds a comment is skipped.
+    fn check_gaps(
+        &mut self,
+        operator: &TokenReference,
+        gaps: [Option<(&TokenReference, &TokenReference)>; 2],
+        expected: [&str; 2],
+        msg: String,
+    ) {
+        let mut edits = vec![];
+        for (i, tokens) in gaps.iter().enumerate() {
+            let (before, after) = match tokens {
+                Some(tokens) => tokens,
+                None => continue,
+            };
+            if gap(before, after).is_some_and(|gap| gap != expected[i]) {
+                let start = before.token().end_position().bytes();
+                let end = after.token().start_position().bytes();
+                edits.push(TextEdit::replace(start, end, expected[i]));
+            }
+        }
+        if !edits.is_empty() {
+            let start = operator.token().start_position();
+            self.reports.push(LintReport {
+                pos: Pos::new(start.line(), start.character()),
+                level: ReportLevel::Warning,
+                msg,
+                fix: Some(Fix::safe(edits)),
+            });
+        }
+    }
+
+    pub fn enter_expr(rctx: &mut dyn RuleContext, node: NodeWrapper) -> NodeWrapper {
+        let expression = rule_cast!(node, NodeWrapper::Expression);
+        let ctx: &mut OperatorSpacing = rctx.downcast_mut().unwrap();
+
+        match &expression {
+            Expression::BinaryOperator { lhs, binop, rhs } => ctx.check_binop(lhs, binop, rhs),
+            Expression::UnaryOperator { unop, expression } => ctx.check_unop(unop, expression),
+            _ => {}
+        }
+        NodeWrapper::Expression(expression)
+    }
+
+    fn check_binop(&mut self, lhs: &Expression, binop: &BinOp, rhs: &Expression) {
+        let exempt = match binop {
+            BinOp::TwoDots(_) => !self.check_concat,
+            BinOp::Caret(_) => self.exempt_caret,
+            _ => false,
+        };
+        let (lhs_end, rhs_start) = match (last_token(lhs), first_token(rhs)) {
+            (Some(lhs_end), Some(rhs_start)) if !exempt => (lhs_end, rhs_start),
+            _ => return,
+        };
+        let operator = binop.token();
+        let msg = format!("Operator `{}` should have one space on each side", operator.token());
+        self.check_gaps(
+            operator,
+            [Some((lhs_end, operator)), Some((operator, rhs_start))],
+            [" ", " "],
+            msg,
+        );
+    }
+
+    fn check_unop(&mut self, unop: &UnOp, expression: &Expression) {
+        let operand_start = match (unop, first_token(expression)) {
+            // `not` is a keyword, `- -x` must keep its space
+            (UnOp::Not(_), _) | (_, None) => return,
+            (UnOp::Minus(_), Some(start)) if start.token().to_string().starts_with('-') => return,
+            (_, Some(start)) => start,
+        };
+        let operator = unop.token();
+        let (expected, msg) = if self.space_after_unary {
+            (" ", format!("Unary operator `{}` should be followed by one space", operator.token()))
+        } else {
+            ("", format!("Unary operator `{}` should not be followed by a space", operator.token()))
+        };
+        self.check_gaps(operator, [None, Some((operator, operand_start))], ["", expected], msg);
+    }
+
+    /// Checks the spaces around the `=` between `lhs_end` and `rhs_start`.
+    fn check_equal(
+        &mut self,
+        lhs_end: Option<&TokenReference>,
+        equal: &TokenReference,
+        rhs_start: Option<&TokenReference>,
+    ) {
+        let msg = "Assignment `=` should have one space on each side".to_string();
+        self.check_gaps(
+            equal,
+            [lhs_end.map(|end| (end, equal)), rhs_start.map(|start| (equal, start))],
+            [" ", " "],
+            msg,
+        );
+    }
+
+    pub fn enter_assignment(rctx: &mut dyn RuleContext, node: NodeWrapper) -> NodeWrapper {
+        let assignment: Assignment = rule_cast!(node, NodeWrapper::Assignment);
+        let ctx: &mut OperatorSpacing = rctx.downcast_mut().unwrap();
+
+        ctx.check_equal(
+            last_token(assignment.variables()),
+            assignment.equal_token(),
+            first_token(assignment.expressions()),
+        );
+        NodeWrapper::Assignment(assignment)
+    }
+
+    pub fn enter_local_assign(rctx: &mut dyn RuleContext, node: NodeWrapper) -> NodeWrapper {
+        let las: LocalAssignment = rule_cast!(node, NodeWrapper::LocalAssignment);
+        let ctx: &mut OperatorSpacing = rctx.downcast_mut().unwrap();
+
+        if let Some(equal) = las.equal_token() {
+            // `local x <const> = 1` ends its names with the `>` of an attribute
+            #[cfg(feature = "lua54")]
+            let lhs_end = match las.attributes().last().flatten() {
+                Some(attribute) => last_token(attribute),
+                None => last_token(las.names()),
+            };
+            #[cfg(not(feature = "lua54"))]
+            let lhs_end = last_token(las.names());
+            ctx.check_equal(lhs_end, equal, first_token(las.expressions()));
+        }
+        NodeWrapper::LocalAssignment(las)
+    }
+}
@@ -0,0 +1,174 @@
+use serde_json::{json, Value};
+
+use super::RuleInfo;
+
+/// The default value of an option, which also gives its type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OptionValue {
+    /// a non-negative integer
+    Integer(u64),
+    Boolean(bool),
+    String(&'static str),
+    StringList(&'static [&'static str]),
+}
+
+impl OptionValue {
+    fn matches(&self, value: &Value) -> bool {
+        match self {
+            OptionValue::Integer(_) => value.is_u64(),
+            OptionValue::Boolean(_) => value.is_boolean(),
+            OptionValue::String(_) => value.is_string(),
+            OptionValue::StringList(_) => {
+                value.as_array().is_some_and(|items| items.iter().all(Value::is_string))
+            }
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            OptionValue::Integer(_) => "a non-negative integer",
+            OptionValue::Boolean(_) => "a boolean",
+            OptionValue::String(_) => "a string",
+            OptionValue::StringList(_) => "a list of strings",
+        }
+    }
+
+    pub fn to_json(self) -> Value {
+        match self {
+            OptionValue::Integer(n) => json!(n),
+            OptionValue::Boolean(b) => json!(b),
+            OptionValue::String(s) => json!(s),
+            OptionValue::StringList(list) => json!(list),
+        }
+    }
+}
+
+/// Checks a value of the right type further, e.g. against a list of names.
+pub type OptionCheck = fn(&Value) -> Result<(), String>;
+
+/// An option a rule reads from its config table.
+#[derive(Clone, Copy, Debug)]
+pub struct RuleOption {
+    pub name: &'static str,
+    pub default: OptionValue,
+    pub description: &'static str,
+    pub check: Option<OptionCheck>,
+}
+
+impl RuleOption {
+    pub const fn new(name: &'static str, default: OptionValue, description: &'static str) -> Self {
+        Self { name, default, description, check: None }
+    }
+
+    pub const fn with_check(mut self, check: OptionCheck) -> Self {
+        self.check = Some(check);
+        self
+    }
+}
+
+/// Every rule accepts it, see `LinterBuilder::with_severity`.
+pub const SEVERITY: &str = "severity";
+const SEVERITY_LEVELS: [&str; 4] = ["off", "info", "warning", "error"];
+
+impl RuleInfo {
+    /// Checks `config` against the declared options: unknown keys and values
+    /// of the wrong type are errors naming the rule and the key.
+    pub fn validate(&self, config: &Value) -> Result<(), String> {
+        let config = match config {
+            Value::Object(config) => config,
+            other => {
+                return Err(format!(
+                    "rule `{}`: expected a table of options, got {other}",
+                    self.name
+                ))
+            }
+        };
+        for (key, value) in config {
+            if key == SEVERITY {
+                if !value.as_str().is_some_and(|level| SEVERITY_LEVELS.contains(&level)) {
+                    return Err(format!(
+                        "rule `{}`: option `{key}` must be one of {}, got {value}",
+                        self.name,
+                        SEVERITY_LEVELS.join(", ")
+                    ));
+                }
+                continue;
+            }
+            match self.options.iter().find(|option| option.name == key) {
+                Some(option) if option.default.matches(value) => {
+                    if let Some(Err(e)) = option.check.map(|check| check(value)) {
+                        return Err(format!("rule `{}`: option `{key}`: {e}", self.name));
+                    }
+                }
+                Some(option) => {
+                    return Err(format!(
+                        "rule `{}`: option `{key}` must be {}, got {value}",
+                        self.name,
+                        option.default.type_name()
+                    ))
+                }
+                None => {
+                    let mut known: Vec<&str> = self.options.iter().map(|o| o.name).collect();
+                    known.push(SEVERITY);
+                    return Err(format!(
+                        "rule `{}`: unknown option `{key}`, expected one of {}",
+                        self.name,
+                        known.join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The value of option `name` in `config`, or its default. `config` was
+    /// checked with `validate` when the rule was added, a value it would reject
+    /// is a bug.
+    pub fn option(&self, config: &Value, name: &str) -> Value {
+        let option = self.options.iter().find(|option| option.name == name);
+        match (config.get(name), option) {
+            (Some(value), Some(option)) => {
+                let valid = option.default.matches(value)
+                    && option.check.is_none_or(|check| check(value).is_ok());
+                assert!(valid, "rule `{}`: option `{name}` was not validated", self.name);
+                value.clone()
+            }
+            (Some(value), None) => value.clone(),
+            (None, Some(option)) => option.default.to_json(),
+            (None, None) => Value::Null,
+        }
+    }
+
+    /// JSON Schema of the rule's entry in the `rules` table of a config file.
+    pub fn json_schema(&self) -> Value {
+        let mut properties = serde_json::Map::new();
+        for option in self.options {
+            let ty = match option.default {
+                OptionValue::Integer(_) => json!({ "type": "integer", "minimum": 0 }),
+                OptionValue::Boolean(_) => json!({ "type": "boolean" }),
+                OptionValue::String(_) => json!({ "type": "string" }),
+                OptionValue::StringList(_) => {
+                    json!({ "type": "array", "items": { "type": "string" } })
+                }
+            };
+            let mut schema = ty.as_object().unwrap().clone();
+            schema.insert("default".to_string(), option.default.to_json());
+            schema.insert("description".to_string(), json!(option.description));
+            properties.insert(option.name.to_string(), Value::Object(schema));
+        }
+        properties.insert(
+            SEVERITY.to_string(),
+            json!({
+                "enum": SEVERITY_LEVELS,
+                "description": "Level of the reports, `off` disables the rule",
+            }),
+        );
+        json!({
+            "description": self.description,
+            "anyOf": [
+                { "type": "boolean" },
+                { "type": "object", "properties": properties, "additionalProperties": false },
+            ],
+        })
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use crate::lint::{
+    scope::{Variable, VariableKind},
+    ScopeAnalysis,
+};
+
+use super::{
+    std_globals::{self, Fields},
+    LintReport, OptionValue, Registry, ReportLevel, Rule, RuleContext, RuleInfo, RuleOption,
+};
+
+decl_rule!(
+    shadowing,
+    Shadowing,
+    "Locals should not hide outer locals, upvalues or standard globals",
+    "20231017",
+    "allow_same_scope: false, std: \"ngx_lua\"",
+    [
+        RuleOption::new(
+            "allow_same_scope",
+            OptionValue::Boolean(false),
+            "Allow declaring a name again in the same scope, e.g. `local err; local err`"
+        ),
+        RuleOption::new(
+            "std",
+            OptionValue::String("ngx_lua"),
+            "`+`-separated std presets whose globals may not be hidden, see `undefined_global`"
+        )
+        .with_check(std_globals::check_std)
+    ]
+);
+pub struct Shadowing {
+    pub reports: Vec<LintReport>,
+
+    allow_same_scope: bool,
+    std_globals: BTreeMap<String, Fields>,
+}
+
+impl Rule for Shadowing {
+    fn apply(rules: &mut Registry, config: &serde_json::Value) -> Self {
+        let rule_name = "shadowing";
+        rules.listen_scopes(rule_name, Self::on_scopes);
+
+        let allow_same_scope = RULE_INFO.option(config, "allow_same_scope").as_bool().unwrap();
+        // `std` was checked along with the other options
+        let std = RULE_INFO.option(config, "std");
+        let std_globals = std_globals::std_globals(std.as_str().unwrap()).unwrap();
+
+        Self { reports: vec![], allow_same_scope, std_globals }
+    }
+
+    fn context(&self) -> &dyn RuleContext {
+        self
+    }
+}
+
+impl RuleContext for Shadowing {
+    fn get_reports(&self) -> &Vec<LintReport> {
+        &self.reports
+    }
+}
+
+impl Shadowing {
+    pub fn on_scopes(rctx: &mut dyn RuleContext, scopes: &ScopeAnalysis) {
+        let ctx: &mut Shadowing = rctx.downcast_mut().unwrap();
+
+        for variable in &scopes.variables {
+            let what = match variable.kind {
+                VariableKind::Local | VariableKind::LocalFunction => "Local",
+                VariableKind::Parameter => "Parameter",
+                VariableKind::ForVariable => "Loop variable",
+                // implicit, nothing to rename
+                VariableKind::SelfParameter | VariableKind::Vararg => continue,
+            };
+            if variable.name.starts_with('_') {
+                continue;
+            }
+            let name = &variable.name;
+            let msg = match variable.shadowed.map(|shadowed| &scopes.variables[shadowed]) {
+                Some(shadowed) if shadowed.scope == variable.scope => {
+                    if ctx.allow_same_scope {
+                        continue;
+                    }
+                    format!(
+                        "{what} `{name}` redeclares `{name}` declared at {} in the same scope",
+                        position(shadowed)
+                    )
+                }
+                Some(shadowed) => {
+                    let upvalue =
+                        scopes.function_of(shadowed.scope) != scopes.function_of(variable.scope);
+                    let outer = match shadowed.kind {
+                        _ if upvalue => "upvalue",
+                        VariableKind::Parameter | VariableKind::SelfParameter => "parameter",
+                        VariableKind::ForVariable => "loop variable",
+                        _ => "local",
+                    };
+                    format!(
+                        "{what} `{name}` shadows the {outer} `{name}` declared at {}",
+                        position(shadowed)
+                    )
+                }
+                // `local type = type` keeps the global at hand rather than hiding it
+                None if ctx.std_globals.contains_key(name)
+                    && !is_global_alias(scopes, variable) =>
+                {
+                    format!("{what} `{name}` shadows the standard global `{name}`")
+                }
+                None => continue,
+            };
+            ctx.reports.push(LintReport {
+                pos: variable.pos.clone(),
+                level: ReportLevel::Warning,
+                msg,
+                fix: None,
+            });
+        }
+    }
+}
+
+fn position(variable: &Variable) -> String {
+    format!("{}:{}", variable.pos.line, variable.pos.col)
+}
+
+/// Whether `variable` is initialized with the global of the same name.
+fn is_global_alias(scopes: &ScopeAnalysis, variable: &Variable) -> bool {
+    variable.alias_of.is_some_and(|reference| {
+        let reference = &scopes.references[reference];
+        reference.variable.is_none() && reference.name == variable.name
+    })
+}
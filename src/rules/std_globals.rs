@@ -0,0 +1,375 @@
+//! The globals defined by Lua implementations and the environments Lua code
+//! runs in, which `undefined_global` accepts and `shadowing` protects.
+
+use std::collections::BTreeMap;
+
+/// A global of a preset.
+#[derive(Clone, Copy, Debug)]
+pub struct StdGlobal {
+    pub name: &'static str,
+    /// the fields of a library table, `None` if any field may be used, e.g.
+    /// for functions and tables that are filled at run time
+    pub fields: Option<&'static [&'static str]>,
+}
+
+const fn any(name: &'static str) -> StdGlobal {
+    StdGlobal { name, fields: None }
+}
+
+const fn table(name: &'static str, fields: &'static [&'static str]) -> StdGlobal {
+    StdGlobal { name, fields: Some(fields) }
+}
+
+const LUA51: &[StdGlobal] = &[
+    any("_G"),
+    any("_VERSION"),
+    any("arg"),
+    any("assert"),
+    any("collectgarbage"),
+    any("dofile"),
+    any("error"),
+    any("gcinfo"),
+    any("getfenv"),
+    any("getmetatable"),
+    any("ipairs"),
+    any("load"),
+    any("loadfile"),
+    any("loadstring"),
+    any("module"),
+    any("newproxy"),
+    any("next"),
+    any("pairs"),
+    any("pcall"),
+    any("print"),
+    any("rawequal"),
+    any("rawget"),
+    any("rawset"),
+    any("require"),
+    any("select"),
+    any("setfenv"),
+    any("setmetatable"),
+    any("tonumber"),
+    any("tostring"),
+    any("type"),
+    any("unpack"),
+    any("xpcall"),
+    table("coroutine", &["create", "resume", "running", "status", "wrap", "yield"]),
+    table(
+        "debug",
+        &[
+            "debug",
+            "getfenv",
+            "gethook",
+            "getinfo",
+            "getlocal",
+            "getmetatable",
+            "getregistry",
+            "getupvalue",
+            "setfenv",
+            "sethook",
+            "setlocal",
+            "setmetatable",
+            "setupvalue",
+            "traceback",
+        ],
+    ),
+    table(
+        "io",
+        &[
+            "close", "flush", "input", "lines", "open", "output", "popen", "read", "stderr",
+            "stdin", "stdout", "tmpfile", "type", "write",
+        ],
+    ),
+    table(
+        "math",
+        &[
+            "abs",
+            "acos",
+            "asin",
+            "atan",
+            "atan2",
+            "ceil",
+            "cos",
+            "cosh",
+            "deg",
+            "exp",
+            "floor",
+            "fmod",
+            "frexp",
+            "huge",
+            "ldexp",
+            "log",
+            "log10",
+            "max",
+            "min",
+            "modf",
+            "pi",
+            "pow",
+            "rad",
+            "random",
+            "randomseed",
+            "sin",
+            "sinh",
+            "sqrt",
+            "tan",
+            "tanh",
+        ],
+    ),
+    table(
+        "os",
+        &[
+            "clock",
+            "date",
+            "difftime",
+            "execute",
+            "exit",
+            "getenv",
+            "remove",
+            "rename",
+            "setlocale",
+            "time",
+            "tmpname",
+        ],
+    ),
+    table("package", &["cpath", "loaded", "loaders", "loadlib", "path", "preload", "seeall"]),
+    table(
+        "string",
+        &[
+            "byte", "char", "dump", "find", "format", "gmatch", "gsub", "len", "lower", "match",
+            "rep", "reverse", "sub", "upper",
+        ],
+    ),
+    table("table", &["concat", "insert", "maxn", "remove", "sort"]),
+];
+
+/// LuaJIT 2.1 on top of Lua 5.1, with its Lua 5.2 and 5.3 extensions.
+const LUAJIT: &[StdGlobal] = &[
+    table(
+        "bit",
+        &[
+            "arshift", "band", "bnot", "bor", "bswap", "bxor", "lshift", "rol", "ror", "rshift",
+            "tobit", "tohex",
+        ],
+    ),
+    any("jit"),
+    table("coroutine", &["isyieldable"]),
+    table("table", &["move"]),
+];
+
+/// The API of lua-nginx-module on top of LuaJIT.
+const NGX_LUA: &[StdGlobal] = &[
+    any("ndk"),
+    table(
+        "ngx",
+        &[
+            // constants
+            "OK",
+            "ERROR",
+            "AGAIN",
+            "DONE",
+            "DECLINED",
+            "null",
+            "HTTP_GET",
+            "HTTP_HEAD",
+            "HTTP_PUT",
+            "HTTP_POST",
+            "HTTP_DELETE",
+            "HTTP_OPTIONS",
+            "HTTP_MKCOL",
+            "HTTP_COPY",
+            "HTTP_MOVE",
+            "HTTP_PROPFIND",
+            "HTTP_PROPPATCH",
+            "HTTP_LOCK",
+            "HTTP_UNLOCK",
+            "HTTP_PATCH",
+            "HTTP_TRACE",
+            "HTTP_CONTINUE",
+            "HTTP_SWITCHING_PROTOCOLS",
+            "HTTP_OK",
+            "HTTP_CREATED",
+            "HTTP_ACCEPTED",
+            "HTTP_NO_CONTENT",
+            "HTTP_PARTIAL_CONTENT",
+            "HTTP_SPECIAL_RESPONSE",
+            "HTTP_MOVED_PERMANENTLY",
+            "HTTP_MOVED_TEMPORARILY",
+            "HTTP_SEE_OTHER",
+            "HTTP_NOT_MODIFIED",
+            "HTTP_TEMPORARY_REDIRECT",
+            "HTTP_PERMANENT_REDIRECT",
+            "HTTP_BAD_REQUEST",
+            "HTTP_UNAUTHORIZED",
+            "HTTP_PAYMENT_REQUIRED",
+            "HTTP_FORBIDDEN",
+            "HTTP_NOT_FOUND",
+            "HTTP_NOT_ALLOWED",
+            "HTTP_NOT_ACCEPTABLE",
+            "HTTP_REQUEST_TIMEOUT",
+            "HTTP_CONFLICT",
+            "HTTP_GONE",
+            "HTTP_UPGRADE_REQUIRED",
+            "HTTP_TOO_MANY_REQUESTS",
+            "HTTP_CLOSE",
+            "HTTP_ILLEGAL",
+            "HTTP_INTERNAL_SERVER_ERROR",
+            "HTTP_METHOD_NOT_IMPLEMENTED",
+            "HTTP_BAD_GATEWAY",
+            "HTTP_SERVICE_UNAVAILABLE",
+            "HTTP_GATEWAY_TIMEOUT",
+            "HTTP_VERSION_NOT_SUPPORTED",
+            "HTTP_INSUFFICIENT_STORAGE",
+            "STDERR",
+            "EMERG",
+            "ALERT",
+            "CRIT",
+            "ERR",
+            "WARN",
+            "NOTICE",
+            "INFO",
+            "DEBUG",
+            // tables
+            "arg",
+            "config",
+            "ctx",
+            "header",
+            "location",
+            "re",
+            "req",
+            "resp",
+            "shared",
+            "socket",
+            "status",
+            "thread",
+            "timer",
+            "var",
+            "worker",
+            // functions
+            "cookie_time",
+            "crc32_long",
+            "crc32_short",
+            "decode_args",
+            "decode_base64",
+            "encode_args",
+            "encode_base64",
+            "eof",
+            "escape_uri",
+            "exec",
+            "exit",
+            "flush",
+            "get_phase",
+            "headers_sent",
+            "hmac_sha1",
+            "http_time",
+            "is_subrequest",
+            "localtime",
+            "log",
+            "md5",
+            "md5_bin",
+            "now",
+            "on_abort",
+            "parse_http_time",
+            "print",
+            "quote_sql_str",
+            "redirect",
+            "run_worker_thread",
+            "say",
+            "send_headers",
+            "sha1_bin",
+            "sleep",
+            "time",
+            "today",
+            "unescape_uri",
+            "update_time",
+            "utctime",
+        ],
+    ),
+];
+
+/// The PDK of Kong plugins on top of lua-nginx-module.
+const KONG: &[StdGlobal] = &[any("kong")];
+
+/// The test API of busted, usually combined with another preset, e.g. `ngx_lua+busted`.
+const BUSTED: &[StdGlobal] = &[
+    any("after_each"),
+    any("async"),
+    any("before_each"),
+    any("context"),
+    any("describe"),
+    any("expose"),
+    any("finally"),
+    any("insulate"),
+    any("it"),
+    any("lazy_setup"),
+    any("lazy_teardown"),
+    any("match"),
+    any("mock"),
+    any("pending"),
+    any("randomize"),
+    any("setup"),
+    any("spec"),
+    any("spy"),
+    any("strict_setup"),
+    any("strict_teardown"),
+    any("stub"),
+    any("teardown"),
+    any("test"),
+];
+
+/// The presets and the presets they extend.
+pub const PRESETS: &[(&str, &[&[StdGlobal]])] = &[
+    ("lua51", &[LUA51]),
+    ("luajit", &[LUA51, LUAJIT]),
+    ("ngx_lua", &[LUA51, LUAJIT, NGX_LUA]),
+    ("kong", &[LUA51, LUAJIT, NGX_LUA, KONG]),
+    ("busted", &[BUSTED]),
+];
+
+/// The fields a global may be used with, `None` for any field.
+pub type Fields = Option<Vec<String>>;
+
+/// The globals of the presets in `std`, a `+`-separated list of preset names
+/// such as `luajit+busted`. The fields of a global defined by several
+/// presets are merged.
+pub fn std_globals(std: &str) -> Result<BTreeMap<String, Fields>, String> {
+    let mut globals: BTreeMap<String, Fields> = BTreeMap::new();
+    for preset_name in std.split('+').map(str::trim) {
+        let preset = match PRESETS.iter().find(|(name, _)| *name == preset_name) {
+            Some((_, preset)) => preset,
+            None => {
+                let known: Vec<&str> = PRESETS.iter().map(|(name, _)| *name).collect();
+                return Err(format!(
+                    "unknown std preset `{preset_name}`, expected one of {}",
+                    known.join(", ")
+                ));
+            }
+        };
+        for global in preset.iter().flat_map(|globals| globals.iter()) {
+            let fields = global.fields.map(|fields| fields.iter().map(|f| f.to_string()));
+            add_global(&mut globals, global.name, fields.map(Iterator::collect));
+        }
+    }
+    Ok(globals)
+}
+
+/// Checks the `std` option of a rule, see `RuleOption::with_check`.
+pub fn check_std(std: &serde_json::Value) -> Result<(), String> {
+    std_globals(std.as_str().unwrap_or_default()).map(|_| ())
+}
+
+/// Adds `name` with `fields`, merging them with the fields it already has.
+pub fn add_global(globals: &mut BTreeMap<String, Fields>, name: &str, fields: Fields) {
+    match globals.get_mut(name) {
+        None => {
+            globals.insert(name.to_string(), fields);
+        }
+        Some(known) => match (known.as_mut(), fields) {
+            (Some(known), Some(fields)) => {
+                known.extend(fields);
+            }
+            // any field already, or from now on
+            (None, _) => {}
+            (Some(_), None) => *known = None,
+        },
+    }
+}
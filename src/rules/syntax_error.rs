@@ -0,0 +1,41 @@
+use full_moon::{ast::AstError, tokenizer::TokenizerError};
+
+use super::{LintReport, Pos, ReportLevel, RuleContext};
+
+// not a real rule: it can't be enabled, the driver binds it when the source fails to parse
+pub const RULE_NAME: &str = "syntax_error";
+
+pub struct SyntaxError {
+    pub reports: Vec<LintReport>,
+}
+
+impl RuleContext for SyntaxError {
+    fn get_reports(&self) -> &Vec<LintReport> {
+        &self.reports
+    }
+}
+
+impl SyntaxError {
+    pub fn from_tokenizer_error(err: &TokenizerError) -> Self {
+        Self::from_report(err.position().into(), format!("Syntax error: {}", err.error()))
+    }
+
+    pub fn from_ast_error(err: &AstError) -> Self {
+        match err {
+            AstError::UnexpectedToken { token, additional } => {
+                let msg = match additional {
+                    Some(additional) => {
+                        format!("Syntax error: unexpected token `{token}`, {additional}")
+                    }
+                    None => format!("Syntax error: unexpected token `{token}`"),
+                };
+                Self::from_report(token.start_position().into(), msg)
+            }
+            other => Self::from_report(Pos::new(0, 0), format!("Syntax error: {other}")),
+        }
+    }
+
+    fn from_report(pos: Pos, msg: String) -> Self {
+        Self { reports: vec![LintReport { pos, level: ReportLevel::Error, msg, fix: None }] }
+    }
+}
@@ -1,8 +1,17 @@
 use full_moon::{ast::punctuated::Pair, node::Node};
 
-use super::{LintReport, NodeKey, NodeWrapper, Registry, Rule, RuleContext, RuleInfo};
+use super::{
+    last_token, Fix, LintReport, NodeKey, NodeWrapper, Registry, Rule, RuleContext, RuleInfo,
+    TextEdit,
+};
 
-decl_rule!(table_ctor_comma, "Require comma after last field of table ctor", "20230224", "");
+decl_rule!(
+    table_ctor_comma,
+    TableCtorComma,
+    "Require comma after last field of table ctor",
+    "20230224",
+    ""
+);
 
 pub struct TableCtorComma {
     pub reports: Vec<LintReport>,
@@ -45,11 +54,13 @@ impl TableCtorComma {
             // then we need to check if there is a comma after it
             let close_brace_line = node.braces().tokens().1.start_position().unwrap().line();
             if let Pair::End(f) = last_field {
-                if f.tokens().last().unwrap().end_position().unwrap().line() != close_brace_line {
+                let field_end = last_token(f).unwrap().token().end_position();
+                if field_end.line() != close_brace_line {
                     ctx.reports.push(LintReport {
                         pos: last_field.end_position().unwrap().into(),
                         level: super::ReportLevel::Warning,
-                        msg: "Table constructor should have a comma after the last field.".to_string()
+                        msg: "Table constructor should have a comma after the last field.".to_string(),
+                        fix: Some(Fix::safe(vec![TextEdit::insert(field_end.bytes(), ",")])),
                     });
                 }
             }
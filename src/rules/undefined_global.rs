@@ -0,0 +1,197 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::lint::{
+    scope::{Access, Reference, VariableKind},
+    ScopeAnalysis,
+};
+
+use super::{
+    std_globals::{self, Fields},
+    LintReport, OptionValue, Pos, Registry, ReportLevel, Rule, RuleContext, RuleInfo, RuleOption,
+};
+
+decl_rule!(
+    undefined_global,
+    UndefinedGlobal,
+    "Only read and assign known globals",
+    "20231017",
+    "std: \"ngx_lua\", globals: [\"_M\"], read_globals: [\"kong.table\"]",
+    [
+        RuleOption::new(
+            "std",
+            OptionValue::String("ngx_lua"),
+            "`+`-separated std presets: lua51, luajit, ngx_lua, kong and busted"
+        )
+        .with_check(std_globals::check_std),
+        RuleOption::new(
+            "globals",
+            OptionValue::StringList(&[]),
+            "Globals the code may read and assign, `a.b` allows the field `b` of the global `a`"
+        ),
+        RuleOption::new(
+            "read_globals",
+            OptionValue::StringList(&[]),
+            "Globals the code may read but not assign"
+        )
+    ]
+);
+pub struct UndefinedGlobal {
+    pub reports: Vec<LintReport>,
+
+    /// the known globals and their fields
+    known: BTreeMap<String, Fields>,
+    writable: HashSet<String>,
+}
+
+impl Rule for UndefinedGlobal {
+    fn apply(rules: &mut Registry, config: &serde_json::Value) -> Self {
+        let rule_name = "undefined_global";
+        rules.listen_scopes(rule_name, Self::on_scopes);
+
+        // `std` was checked along with the other options
+        let std = RULE_INFO.option(config, "std");
+        let mut known = std_globals::std_globals(std.as_str().unwrap()).unwrap();
+        let mut writable = HashSet::new();
+        for (option, is_writable) in [("globals", true), ("read_globals", false)] {
+            for global in RULE_INFO.option(config, option).as_array().unwrap() {
+                let global = global.as_str().unwrap();
+                match global.split_once('.') {
+                    Some((name, field)) => {
+                        std_globals::add_global(&mut known, name, Some(vec![field.to_string()]))
+                    }
+                    None => {
+                        std_globals::add_global(&mut known, global, None);
+                        if is_writable {
+                            writable.insert(global.to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        Self { reports: vec![], known, writable }
+    }
+
+    fn context(&self) -> &dyn RuleContext {
+        self
+    }
+}
+
+impl RuleContext for UndefinedGlobal {
+    fn get_reports(&self) -> &Vec<LintReport> {
+        &self.reports
+    }
+}
+
+impl UndefinedGlobal {
+    pub fn on_scopes(rctx: &mut dyn RuleContext, scopes: &ScopeAnalysis) {
+        let ctx: &mut UndefinedGlobal = rctx.downcast_mut().unwrap();
+
+        for reference in &scopes.references {
+            let report = match reference.variable {
+                // the main chunk takes `...`
+                None if reference.name == "..." => None,
+                None => ctx.check_global(reference),
+                // `local ngx = ngx` keeps the fields of `ngx`
+                Some(_) => aliased_global(scopes, reference)
+                    .and_then(|global| ctx.check_field(&global.name, reference)),
+            };
+            if let Some((pos, msg)) = report {
+                ctx.reports.push(LintReport { pos, level: ReportLevel::Warning, msg, fix: None });
+            }
+        }
+    }
+
+    fn check_global(&self, reference: &Reference) -> Option<(Pos, String)> {
+        let name = &reference.name;
+        if !self.known.contains_key(name) {
+            let msg = format!("{} undefined global `{name}`", verb(reference.access));
+            let suggestion = closest_name(name, self.known.keys().map(String::as_str));
+            return Some((reference.pos.clone(), with_suggestion(msg, suggestion)));
+        }
+        if reference.access == Access::Write && !self.writable.contains(name) {
+            return Some((reference.pos.clone(), format!("Setting read-only global `{name}`")));
+        }
+        self.check_field(name, reference)
+    }
+
+    /// Checks the field `reference` indexes against the fields of `global`.
+    fn check_field(&self, global: &str, reference: &Reference) -> Option<(Pos, String)> {
+        let fields = self.known.get(global)?.as_ref()?;
+        let field = reference.field.as_ref()?;
+        if fields.contains(&field.name) {
+            return None;
+        }
+        let msg =
+            format!("{} undefined field `{}` of global `{global}`", verb(field.access), field.name);
+        let suggestion = closest_name(&field.name, fields.iter().map(String::as_str));
+        Some((field.pos.clone(), with_suggestion(msg, suggestion)))
+    }
+}
+
+/// The global `reference` refers to through locals that are never
+/// reassigned, e.g. `ngx` for `log` after `local ngx = ngx; local log = ngx`.
+fn aliased_global<'a>(
+    scopes: &'a ScopeAnalysis,
+    reference: &'a Reference,
+) -> Option<&'a Reference> {
+    let mut reference = reference;
+    while let Some(variable) = scopes.declaration(reference) {
+        let reassigned =
+            variable.references.iter().any(|&id| scopes.references[id].access == Access::Write);
+        if variable.kind != VariableKind::Local || reassigned {
+            return None;
+        }
+        reference = &scopes.references[variable.alias_of?];
+    }
+    Some(reference)
+}
+
+fn verb(access: Access) -> &'static str {
+    match access {
+        Access::Read => "Accessing",
+        Access::Write => "Setting",
+    }
+}
+
+fn with_suggestion(msg: String, suggestion: Option<&str>) -> String {
+    match suggestion {
+        Some(suggestion) => format!("{msg}, did you mean `{suggestion}`?"),
+        None => msg,
+    }
+}
+
+/// The candidate closest to `name`, if it is a plausible typo of it: at most
+/// one edit for every four characters, a swap of two neighbours counting once.
+/// Of equally close candidates the shortest wins.
+fn closest_name<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = ((name.chars().count() + 2) / 4).max(1);
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, candidate)| (*distance, candidate.len()))
+        .map(|(_, candidate)| candidate)
+}
+
+/// Optimal string alignment distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
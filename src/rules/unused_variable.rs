@@ -0,0 +1,107 @@
+use crate::lint::{
+    scope::{Variable, VariableKind},
+    ScopeAnalysis,
+};
+
+use super::{
+    LintReport, OptionValue, Registry, ReportLevel, Rule, RuleContext, RuleInfo, RuleOption,
+};
+
+decl_rule!(
+    unused_variable,
+    UnusedVariable,
+    "Locals, parameters and loop variables should be read",
+    "20231017",
+    "ignore_self: true, ignore_varargs: false",
+    [
+        RuleOption::new(
+            "ignore_self",
+            OptionValue::Boolean(true),
+            "Do not report the implicit `self` of methods"
+        ),
+        RuleOption::new(
+            "ignore_varargs",
+            OptionValue::Boolean(false),
+            "Do not report `...` parameters"
+        )
+    ]
+);
+pub struct UnusedVariable {
+    pub reports: Vec<LintReport>,
+
+    ignore_self: bool,
+    ignore_varargs: bool,
+}
+
+impl Rule for UnusedVariable {
+    fn apply(rules: &mut Registry, config: &serde_json::Value) -> Self {
+        let rule_name = "unused_variable";
+        rules.listen_scopes(rule_name, Self::on_scopes);
+
+        let ignore_self = RULE_INFO.option(config, "ignore_self").as_bool().unwrap();
+        let ignore_varargs = RULE_INFO.option(config, "ignore_varargs").as_bool().unwrap();
+
+        Self { reports: vec![], ignore_self, ignore_varargs }
+    }
+
+    fn context(&self) -> &dyn RuleContext {
+        self
+    }
+}
+
+impl RuleContext for UnusedVariable {
+    fn get_reports(&self) -> &Vec<LintReport> {
+        &self.reports
+    }
+}
+
+impl UnusedVariable {
+    pub fn on_scopes(rctx: &mut dyn RuleContext, scopes: &ScopeAnalysis) {
+        let ctx: &mut UnusedVariable = rctx.downcast_mut().unwrap();
+
+        for (id, variable) in scopes.variables.iter().enumerate() {
+            if !ctx.is_checked(variable) || scopes.reads(id).next().is_some() {
+                continue;
+            }
+            let what = match variable.kind {
+                VariableKind::Local => "local variable",
+                VariableKind::LocalFunction => "local function",
+                VariableKind::Parameter | VariableKind::SelfParameter => "parameter",
+                VariableKind::Vararg => "variable length argument",
+                VariableKind::ForVariable => "loop variable",
+            };
+            let msg = match variable.kind {
+                VariableKind::Vararg => format!("Unused {what}"),
+                // only written, e.g. `local a; a = 1`
+                _ if !variable.references.is_empty() => {
+                    format!("{} `{}` is assigned but never read", capitalize(what), variable.name)
+                }
+                _ => format!("Unused {what} `{}`", variable.name),
+            };
+            ctx.reports.push(LintReport {
+                pos: variable.pos.clone(),
+                level: ReportLevel::Warning,
+                msg,
+                fix: None,
+            });
+        }
+    }
+
+    /// Whether `variable` may be reported, names starting with `_` are unused
+    /// on purpose.
+    fn is_checked(&self, variable: &Variable) -> bool {
+        match variable.kind {
+            VariableKind::SelfParameter => !self.ignore_self,
+            VariableKind::Vararg => !self.ignore_varargs,
+            _ => !variable.name.starts_with('_'),
+        }
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
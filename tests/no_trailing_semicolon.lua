@@ -8,8 +8,13 @@ end
 --this is allowed ;
 --[[
     ;
-this is not allowed                 
+this is allowed too
 ]]
+local s = "a;"
+a = 1; b = 2 -- allowed with `allow_separator`
+local f = g;
+(f or print)("the semicolon keeps this from calling `g`")
 if a then
     b(); -- this is not allowed
-end
\ No newline at end of file
+    return;
+end